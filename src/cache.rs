@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Internal IDs of the user stories looked up so far, so later runs can skip the lookup, and
+/// digests of the descriptions `plan` compared, so it only reads the stories changed since.
+///
+/// Lives in `$XDG_CACHE_HOME/backlogr/refs.json` (or `~/.cache/backlogr/refs.json`). Refs and
+/// IDs are never reused by Taiga, so an entry can only go stale when its story is deleted.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RefCache {
+    /// Story ID by ref, per `<api url>#<project id>`.
+    #[serde(default)]
+    stories: BTreeMap<String, BTreeMap<usize, usize>>,
+    /// Version of a story and digest of its description at that version, per API URL and
+    /// story ID.
+    #[serde(default)]
+    descriptions: BTreeMap<String, BTreeMap<usize, (usize, u64)>>,
+    /// Where the cache was loaded from; a cache without a path is kept in memory only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl RefCache {
+    /// Location of the cache file for the current user.
+    pub fn path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("refs.json"))
+    }
+
+    /// Loads the cache of the current user.
+    ///
+    /// The cache is only an optimization: a missing or unreadable file gives an empty cache.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        let mut cache = Self::read(&path).unwrap_or_default();
+        cache.path = Some(path);
+        cache
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Writes the cache back to the file it was loaded from.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the file cannot be written.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Saves the cache if possible.
+    ///
+    /// The cache only saves requests, so a run must not fail because it cannot be written.
+    pub fn persist(&self) {
+        let _ = self.save();
+    }
+
+    pub fn story_id(&self, api_url: &str, project_id: usize, reference: usize) -> Option<usize> {
+        self.stories
+            .get(&project_key(api_url, project_id))?
+            .get(&reference)
+            .copied()
+    }
+
+    pub fn insert_story(&mut self, api_url: &str, project_id: usize, reference: usize, id: usize) {
+        self.stories
+            .entry(project_key(api_url, project_id))
+            .or_default()
+            .insert(reference, id);
+    }
+
+    /// Drops the entry of a deleted story.
+    pub fn remove_story(&mut self, api_url: &str, id: usize) {
+        let prefix = format!("{api_url}#");
+
+        for (_, refs) in self
+            .stories
+            .iter_mut()
+            .filter(|(key, _)| key.starts_with(&prefix))
+        {
+            refs.retain(|_, story_id| *story_id != id);
+        }
+
+        if let Some(descriptions) = self.descriptions.get_mut(api_url) {
+            descriptions.remove(&id);
+        }
+    }
+
+    /// Whether the story was seen with this description at this very version.
+    ///
+    /// Any edit in Taiga bumps the version, so a match means the description did not change.
+    pub fn has_description(
+        &self,
+        api_url: &str,
+        id: usize,
+        version: usize,
+        description: &str,
+    ) -> bool {
+        self.descriptions
+            .get(api_url)
+            .and_then(|descriptions| descriptions.get(&id))
+            .is_some_and(|seen| *seen == (version, digest(description)))
+    }
+
+    pub fn insert_description(
+        &mut self,
+        api_url: &str,
+        id: usize,
+        version: usize,
+        description: &str,
+    ) {
+        self.descriptions
+            .entry(api_url.to_owned())
+            .or_default()
+            .insert(id, (version, digest(description)));
+    }
+}
+
+/// Digest of a description, ignoring the surrounding whitespace as `plan` does.
+fn digest(description: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    description.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn project_key(api_url: &str, project_id: usize) -> String {
+    format!("{api_url}#{project_id}")
+}
+
+/// `$XDG_CACHE_HOME/backlogr`, or `~/.cache/backlogr`.
+fn cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("backlogr"))
+}
@@ -1,6 +1,15 @@
-use clap::{command, crate_version, Parser, Subcommand, ValueEnum};
+use clap::{crate_version, Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use std::{collections::HashSet, ops::RangeInclusive, path::PathBuf};
 
-use crate::integrations::taiga::Status;
+use crate::{
+    dates::Date,
+    hooks::ClosedPolicy,
+    integrations::taiga::{
+        ChangelogStyle, Column, GroupBy, IssueType, Priority, Severity, SortKey, Status,
+        StoryFilter,
+    },
+};
 
 #[derive(Parser)]
 #[command(version, about,  long_about = None, before_help = format!(r#"
@@ -16,17 +25,37 @@ use crate::integrations::taiga::Status;
     ))
 ]
 pub struct Cli {
+    /// Path to the config file [default: $XDG_CONFIG_HOME/backlogr/config.toml]
+    #[arg(long = "config", env = "BACKLOGR_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Profile of the config file to use
+    #[arg(long = "profile", env = "BACKLOGR_PROFILE", global = true)]
+    pub profile: Option<String>,
+
+    /// Taiga API URL, for self-hosted instances [default: https://api.taiga.io/api/v1]
+    #[arg(long = "api-url", env = "TAIGA_URL")]
+    pub api_url: Option<String>,
+
     /// Taiga Username
-    #[arg(long = "username", env = "USERNAME", required = true)]
-    pub username: String,
+    #[arg(long = "username", env = "USERNAME")]
+    pub username: Option<String>,
 
-    /// Taiga password
-    #[arg(long = "password", env = "PASSWORD", required = true)]
-    pub password: String,
+    /// Taiga password, not needed after `backlogr login`
+    #[arg(long = "password", env = "PASSWORD")]
+    pub password: Option<String>,
 
     /// Taiga project name
-    #[arg(long = "project_name", env = "PROJECT_NAME", required = true)]
-    pub project_name: String,
+    #[arg(long = "project_name", env = "PROJECT_NAME")]
+    pub project_name: Option<String>,
+
+    /// Output format [default: pretty]
+    #[arg(short, long = "format", value_enum, global = true)]
+    pub format: Option<Format>,
+
+    /// Only print results and errors, not progress
+    #[arg(short, long = "quiet", global = true)]
+    pub quiet: bool,
 
     #[command(subcommand)]
     pub command: Option<Command>,
@@ -36,7 +65,11 @@ impl Cli {
     #[must_use]
     pub fn command(&self) -> Command {
         self.command.clone().unwrap_or(Command::List {
-            format: Format::Pretty,
+            filter: StoryFilter::default(),
+            sort: None,
+            columns: Vec::new(),
+            template: None,
+            template_file: None,
         })
     }
 }
@@ -49,24 +82,449 @@ pub enum Command {
         subject: String,
         #[arg(long = "description")]
         description: Option<String>,
-        #[arg(long = "description", value_enum, default_value_t = Status::New)]
+        #[arg(long = "status", value_enum, default_value_t = Status::New)]
         status: Status,
+        /// Return the existing story with the same subject instead of creating another one
+        #[arg(long = "if-not-exists")]
+        if_not_exists: bool,
+        /// Match the existing story on this key, stored as the tag `key:<KEY>`, rather than on
+        /// the subject; implies --if-not-exists
+        #[arg(long = "idempotency-key", value_name = "KEY")]
+        idempotency_key: Option<String>,
+    },
+    /// Updates User Stories to 'In Progress', e.g. `wip 12 15 20-24`
+    Wip {
+        #[command(flatten)]
+        stories: StorySelection,
+    },
+    /// Updates User Stories to 'Done', e.g. `done 12 15 20-24`
+    Done {
+        #[command(flatten)]
+        stories: StorySelection,
+    },
+    /// Deletes User Stories, e.g. `delete 12 15 20-24`
+    Delete {
+        #[command(flatten)]
+        stories: StorySelection,
+    },
+    /// Shows the full detail of a User Story
+    Show { story_id: usize },
+    /// Edits the fields of a User Story, in $EDITOR when no flag is given
+    Edit {
+        story_id: usize,
+        #[arg(long = "subject")]
+        subject: Option<String>,
+        #[arg(long = "description")]
+        description: Option<String>,
+        /// Replaces the tags of the story (repeatable)
+        #[arg(long = "tag", conflicts_with = "no_tags")]
+        tag: Vec<String>,
+        /// Removes all the tags of the story
+        #[arg(long = "no-tags")]
+        no_tags: bool,
+        /// Username or full name of a project member
+        #[arg(long = "assignee", conflicts_with = "unassign")]
+        assignee: Option<String>,
+        #[arg(long = "unassign")]
+        unassign: bool,
+        /// Point value for the estimating role, e.g. "3" or "?"
+        #[arg(long = "points")]
+        points: Option<String>,
+        /// YYYY-MM-DD
+        #[arg(long = "due-date", conflicts_with = "no_due_date")]
+        due_date: Option<Date>,
+        #[arg(long = "no-due-date")]
+        no_due_date: bool,
+        /// Marks the story as blocked, optionally with the reason
+        #[arg(
+            long = "blocked",
+            value_name = "NOTE",
+            num_args = 0..=1,
+            default_missing_value = "",
+            conflicts_with = "unblock"
+        )]
+        blocked: Option<String>,
+        #[arg(long = "unblock")]
+        unblock: bool,
+    },
+    /// Moves a User Story to another status or Sprint
+    Move {
+        story_id: usize,
+        /// Name or slug of any status of the project, e.g. "Ready for test"
+        #[arg(long = "to", required_unless_present = "sprint")]
+        to: Option<String>,
+        /// Name of the Sprint
+        #[arg(long = "sprint")]
+        sprint: Option<String>,
+    },
+    /// Moves the User Stories referenced in commit messages, e.g. `fix: login bug (#42)`
+    FromCommit {
+        /// Read the commit message from this file instead of stdin
+        #[arg(long = "file", conflicts_with = "range")]
+        file: Option<PathBuf>,
+        /// Read the messages of the commits in this git revision range, e.g. `main..HEAD`
+        #[arg(long = "range")]
+        range: Option<String>,
+        /// SHA of the commit whose message is read from stdin or --file
+        #[arg(long = "sha", conflicts_with = "range")]
+        sha: Option<String>,
+        /// Print the changes without applying them
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Creates many User Stories from a CSV, JSON or YAML file
+    ///
+    /// Columns: subject, description, status, tags, assignee, epic (ref) and sprint (name).
+    /// Every row is checked first; nothing is created if any row is invalid.
+    Import {
+        /// File with one story per row (.csv) or a list of stories (.json, .yaml)
+        file: PathBuf,
+        /// Only check the rows, without creating anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Shows the changes that would bring the project in line with a backlog file
+    ///
+    /// Stories are matched by their `id`, stored in Taiga as the tag `ext:<id>`.
+    Plan {
+        /// Backlog file with the desired stories (.yaml, .json or .csv)
+        file: PathBuf,
+    },
+    /// Creates, updates and closes User Stories to match a backlog file
+    Apply {
+        /// Backlog file with the desired stories (.yaml, .json or .csv)
+        file: PathBuf,
+    },
+    /// Writes release notes from the User Stories referenced in commits
+    Changelog {
+        /// Git revision range of the release, e.g. `v1.1.0..v1.2.0`
+        #[arg(long = "range", required_unless_present_any = ["since", "until"])]
+        range: Option<String>,
+        /// Only commits made on or after this date (YYYY-MM-DD)
+        #[arg(long = "since")]
+        since: Option<Date>,
+        /// Only commits made before this date (YYYY-MM-DD)
+        #[arg(long = "until")]
+        until: Option<Date>,
+        #[arg(long = "group-by", value_enum, default_value_t = GroupBy::Tag)]
+        group_by: GroupBy,
+        /// Layout of the text output; JSON and YAML come from --format
+        #[arg(long = "style", value_enum, default_value_t = ChangelogStyle::Markdown)]
+        style: ChangelogStyle,
+        /// Name of the release, e.g. "1.2.0" [default: Unreleased]
+        #[arg(long = "release")]
+        release: Option<String>,
+        /// Also list the stories that are still open
+        #[arg(long = "include-open")]
+        include_open: bool,
+    },
+    /// Installs and runs the git hooks that check the story refs of commits
+    Hooks {
+        #[command(subcommand)]
+        command: HooksCommand,
+    },
+    /// Manages the Tasks of User Stories
+    Task {
+        #[command(subcommand)]
+        command: TaskCommand,
+    },
+    /// Manages Issues
+    Issue {
+        #[command(subcommand)]
+        command: IssueCommand,
+    },
+    /// Manages Epics and the User Stories linked to them
+    Epic {
+        #[command(subcommand)]
+        command: EpicCommand,
     },
-    /// Updates a User Story to 'In Progress'
-    Wip { story_id: usize },
-    /// Updates a User Story to 'Done'
-    Done { story_id: usize },
-    /// Deletes a User Story
-    Delete { story_id: usize },
+    /// Manages Sprints (milestones)
+    Sprint {
+        #[command(subcommand)]
+        command: SprintCommand,
+    },
+    /// Stores a Taiga session so later runs don't need a password
+    Login,
+    /// Removes the stored Taiga session
+    Logout,
     /// List User stories
     List {
-        #[arg(short, long = "format", value_enum, default_value_t = Format::Pretty)]
-        format: Format,
+        #[command(flatten)]
+        filter: StoryFilter,
+        /// Order of the stories within each status
+        #[arg(long = "sort", value_enum)]
+        sort: Option<SortKey>,
+        /// Extra fields to show, comma separated
+        #[arg(long = "columns", value_enum, value_delimiter = ',')]
+        columns: Vec<Column>,
+        /// Jinja template rendered for each story, e.g. '{{ref}}\t{{status}}\t{{subject}}'
+        #[arg(long = "template", conflicts_with = "template_file")]
+        template: Option<String>,
+        /// File with a Jinja template; use `stories` or `groups` in it to render the whole list at once
+        #[arg(long = "template-file")]
+        template_file: Option<PathBuf>,
     },
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+/// User Stories to act on, by ref or by query.
+#[derive(Args, Clone, Debug)]
+pub struct StorySelection {
+    /// Refs of the User Stories, or ranges of refs such as `20-24`
+    #[arg(
+        value_name = "STORY_ID",
+        value_parser = parse_ref_range,
+        required_unless_present = "filter"
+    )]
+    refs: Vec<RangeInclusive<usize>>,
+    /// Also the User Stories matching a query, with the keys of the `list` flags, e.g.
+    /// `status=ready for test,sprint=Sprint 12,open`
+    #[arg(long = "filter", value_name = "QUERY")]
+    pub filter: Option<StoryFilter>,
+}
+
+impl StorySelection {
+    /// The refs given, with the ranges expanded and without duplicates.
+    pub fn refs(&self) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        self.refs
+            .iter()
+            .cloned()
+            .flatten()
+            .filter(|reference| seen.insert(*reference))
+            .collect()
+    }
+}
+
+/// Most refs a single range may span, so that a typo such as `20-2400` fails early.
+const MAX_RANGE_LEN: usize = 500;
+
+/// `12` or `20-24`.
+fn parse_ref_range(value: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |reference: &str| {
+        reference
+            .trim_start_matches('#')
+            .parse::<usize>()
+            .map_err(|_| format!("'{value}' is not a ref or a range of refs such as 20-24"))
+    };
+
+    match value.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                return Err(format!("The range '{value}' is empty"));
+            }
+            if end - start >= MAX_RANGE_LEN {
+                return Err(format!(
+                    "The range '{value}' spans more than {MAX_RANGE_LEN} refs"
+                ));
+            }
+            Ok(start..=end)
+        }
+        None => {
+            let reference = parse(value)?;
+            Ok(reference..=reference)
+        }
+    }
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum HooksCommand {
+    /// Installs commit-msg and pre-push hooks in the current git repository
+    Install {
+        /// What to do with commits that mention a closed User Story
+        #[arg(long = "closed", value_enum, default_value_t = ClosedPolicy::Warn)]
+        closed: ClosedPolicy,
+        /// Replace existing hooks not installed by backlogr
+        #[arg(long = "force")]
+        force: bool,
+    },
+    /// Checks that the story refs mentioned in commits exist in the project
+    Check {
+        /// Read the commit message from this file, as the commit-msg hook does
+        #[arg(long = "file", conflicts_with_all = ["range", "pre_push"])]
+        file: Option<PathBuf>,
+        /// Check the commits in this git revision range, e.g. `origin/main..HEAD`
+        #[arg(long = "range", conflicts_with = "pre_push")]
+        range: Option<String>,
+        /// Check the commits being pushed, read from stdin as the pre-push hook does
+        #[arg(long = "pre-push")]
+        pre_push: bool,
+        /// What to do with commits that mention a closed User Story
+        #[arg(long = "closed", value_enum, default_value_t = ClosedPolicy::Warn)]
+        closed: ClosedPolicy,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum TaskCommand {
+    /// Creates a new Task in a User Story
+    Create {
+        /// Ref of the parent User Story
+        #[arg(long = "story")]
+        story: usize,
+        #[arg(long = "subject")]
+        subject: String,
+        #[arg(long = "description")]
+        description: Option<String>,
+        #[arg(long = "status", value_enum, default_value_t = Status::New)]
+        status: Status,
+    },
+    /// Updates a Task to 'In Progress'
+    Wip { task_id: usize },
+    /// Updates a Task to 'Done'
+    Done { task_id: usize },
+    /// Deletes a Task
+    Delete { task_id: usize },
+    /// List Tasks
+    List {
+        /// Only list the Tasks of the User Story with this ref
+        #[arg(long = "story")]
+        story: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum IssueCommand {
+    /// Creates a new Issue
+    Create {
+        #[arg(long = "subject")]
+        subject: String,
+        #[arg(long = "description")]
+        description: Option<String>,
+        /// Issue type name, e.g. "Bug" [default: project default]
+        #[arg(long = "type")]
+        issue_type: Option<IssueType>,
+        /// Severity name, e.g. "Critical" [default: project default]
+        #[arg(long = "severity")]
+        severity: Option<Severity>,
+        /// Priority name, e.g. "High" [default: project default]
+        #[arg(long = "priority")]
+        priority: Option<Priority>,
+    },
+    /// List Issues
+    List,
+    /// Shows the details of an Issue
+    Show { issue_id: usize },
+    /// Updates the fields of an Issue
+    Update {
+        issue_id: usize,
+        #[arg(long = "subject")]
+        subject: Option<String>,
+        #[arg(long = "description")]
+        description: Option<String>,
+        #[arg(long = "type")]
+        issue_type: Option<IssueType>,
+        #[arg(long = "severity")]
+        severity: Option<Severity>,
+        #[arg(long = "priority")]
+        priority: Option<Priority>,
+        #[arg(long = "status", value_enum)]
+        status: Option<Status>,
+    },
+    /// Closes an Issue
+    Close { issue_id: usize },
+    /// Deletes an Issue
+    Delete { issue_id: usize },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum EpicCommand {
+    /// Creates a new Epic
+    Create {
+        #[arg(long = "subject")]
+        subject: String,
+        #[arg(long = "description")]
+        description: Option<String>,
+        #[arg(long = "status", value_enum, default_value_t = Status::New)]
+        status: Status,
+    },
+    /// List Epics
+    List,
+    /// Shows an Epic with the progress of its User Stories
+    Show { epic_id: usize },
+    /// Links a User Story to an Epic
+    Link { epic_id: usize, story_id: usize },
+    /// Unlinks a User Story from an Epic
+    Unlink { epic_id: usize, story_id: usize },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum SprintCommand {
+    /// List Sprints
+    List,
+    /// Creates a new Sprint
+    Create {
+        #[arg(long = "name")]
+        name: String,
+        /// First day of the Sprint (YYYY-MM-DD) [default: today]
+        #[arg(long = "start")]
+        start: Option<Date>,
+        /// Last day of the Sprint (YYYY-MM-DD) [default: two weeks after the start]
+        #[arg(long = "finish")]
+        finish: Option<Date>,
+    },
+    /// Shows a Sprint with its User Stories
+    Show {
+        /// Name of the Sprint [default: the Sprint running today]
+        name: Option<String>,
+    },
+    /// Closes a Sprint
+    Close { name: String },
+}
+
+#[derive(Debug, Clone, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Format {
+    #[default]
     Pretty,
     Json,
+    /// One JSON document per line
+    Ndjson,
+    Yaml,
+    Csv,
+    /// Tables grouped by status
+    Markdown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ref_range_accepts_refs_and_ranges() {
+        assert_eq!(parse_ref_range("12"), Ok(12..=12));
+        assert_eq!(parse_ref_range("#12"), Ok(12..=12));
+        assert_eq!(parse_ref_range("20-24"), Ok(20..=24));
+        assert_eq!(parse_ref_range("#20-#24"), Ok(20..=24));
+    }
+
+    #[test]
+    fn parse_ref_range_rejects_other_values() {
+        assert!(parse_ref_range("abc").is_err());
+        assert!(parse_ref_range("20-").is_err());
+        assert!(parse_ref_range("-3").is_err());
+        assert_eq!(
+            parse_ref_range("24-20"),
+            Err("The range '24-20' is empty".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_ref_range_caps_the_range_length() {
+        assert_eq!(parse_ref_range("1-500"), Ok(1..=500));
+        assert_eq!(
+            parse_ref_range("20-2400"),
+            Err("The range '20-2400' spans more than 500 refs".to_owned())
+        );
+    }
+
+    #[test]
+    fn selection_refs_expand_ranges_without_duplicates() {
+        let cli = Cli::try_parse_from(["backlogr", "done", "3", "1-4", "#2"]).unwrap();
+        let Command::Done { stories } = cli.command() else {
+            panic!("expected the done command");
+        };
+
+        assert_eq!(stories.refs(), vec![3, 1, 2, 4]);
+    }
 }
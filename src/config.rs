@@ -0,0 +1,268 @@
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{
+    cli::{Cli, Format},
+    git::CommitRules,
+    integrations::taiga::{StatusMapping, TAIGA_API_URL},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Failed to parse config file {}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Profile '{name}' not found in the config file. Available profiles: {available}")]
+    ProfileNotFound { name: String, available: String },
+    #[error("Environment variable '{0}' set as password_env is not set")]
+    PasswordEnv(String),
+    #[error("password_command `{command}` failed: {reason}")]
+    PasswordCommand { command: String, reason: String },
+    #[error(
+        "No project given. Use --project_name, set PROJECT_NAME or add `project` to your profile"
+    )]
+    NoProject,
+}
+
+/// Contents of the `config.toml` file.
+///
+/// ```toml
+/// default_profile = "work"
+///
+/// [profiles.work]
+/// url = "https://taiga.example.com/api/v1"
+/// username = "lau"
+/// password_env = "WORK_TAIGA_PASSWORD"
+/// project = "Gulfi"
+/// format = "pretty"
+///
+/// [profiles.work.statuses]
+/// wip = ["In progress", "Ready for test"]
+///
+/// [profiles.work.commits.rules]
+/// done = ["fix", "closes"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profile used when `--profile` is not given.
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A named set of defaults for a Taiga instance and project.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Taiga API URL of the instance.
+    pub url: Option<String>,
+    pub username: Option<String>,
+    /// Password in plain text. Prefer `password_env` or `password_command`.
+    pub password: Option<String>,
+    /// Name of the environment variable holding the password.
+    pub password_env: Option<String>,
+    /// Shell command that prints the password, e.g. `pass show taiga`.
+    pub password_command: Option<String>,
+    /// Default project name.
+    pub project: Option<String>,
+    /// Default output format.
+    pub format: Option<Format>,
+    /// Project statuses that stand for `new`, `wip` and `done`.
+    pub statuses: Option<StatusMapping>,
+    /// Keywords of commit messages that move stories, for `from-commit`.
+    pub commits: Option<CommitRules>,
+}
+
+impl Config {
+    /// Default location of the config file: `$XDG_CONFIG_HOME/backlogr/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Loads the config file at `path`, or at the default location if no path is given.
+    ///
+    /// A missing file at the default location is treated as an empty config.
+    ///
+    /// # Errors
+    /// Returns `ConfigError` if the file cannot be read or is not valid.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !explicit => {
+                return Ok(Self::default())
+            }
+            Err(source) => return Err(ConfigError::Io { path, source }),
+        };
+
+        toml::from_str(&content).map_err(|source| ConfigError::Parse { path, source })
+    }
+
+    /// Returns the profile with the given name, or the default one if no name is given.
+    ///
+    /// # Errors
+    /// Returns `ConfigError::ProfileNotFound` if a profile was requested but does not exist.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, ConfigError> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(self.profiles.get("default").cloned().unwrap_or_default());
+        };
+
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ConfigError::ProfileNotFound {
+                name: name.to_owned(),
+                available: if self.profiles.is_empty() {
+                    "none".to_owned()
+                } else {
+                    self.profiles
+                        .keys()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                },
+            })
+    }
+}
+
+impl Profile {
+    /// Resolves the password from whichever source the profile defines.
+    ///
+    /// # Errors
+    /// Returns `ConfigError` if `password_env` is unset or `password_command` fails.
+    pub fn password(&self) -> Result<Option<String>, ConfigError> {
+        if let Some(password) = &self.password {
+            return Ok(Some(password.clone()));
+        }
+
+        if let Some(var) = &self.password_env {
+            return std::env::var(var)
+                .map(Some)
+                .map_err(|_| ConfigError::PasswordEnv(var.clone()));
+        }
+
+        if let Some(command) = &self.password_command {
+            let failed = |reason: String| ConfigError::PasswordCommand {
+                command: command.clone(),
+                reason,
+            };
+
+            let output = process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stderr(process::Stdio::inherit())
+                .output()
+                .map_err(|err| failed(err.to_string()))?;
+
+            if !output.status.success() {
+                return Err(failed(output.status.to_string()));
+            }
+
+            let password =
+                String::from_utf8(output.stdout).map_err(|err| failed(err.to_string()))?;
+            return Ok(Some(password.trim_end_matches(['\r', '\n']).to_owned()));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Effective settings after merging flags, environment variables and the selected profile.
+///
+/// Flags and environment variables always take precedence over the profile.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub api_url: String,
+    pub username: Option<String>,
+    /// Password given with `--password` or `PASSWORD`.
+    pub password: Option<String>,
+    pub project_name: Option<String>,
+    pub format: Format,
+    pub statuses: StatusMapping,
+    pub commits: CommitRules,
+    /// Profile the password is resolved from when a fresh login is needed, see
+    /// [`Settings::login_password`].
+    profile: Profile,
+}
+
+impl Settings {
+    /// Merges the command line with the config file and profile it points to.
+    ///
+    /// # Errors
+    /// Returns `ConfigError` if the config file or profile cannot be loaded.
+    pub fn resolve(cli: &Cli) -> Result<Self, ConfigError> {
+        let config = Config::load(cli.config.as_deref())?;
+        let mut profile = config.profile(cli.profile.as_deref())?;
+
+        let api_url = cli
+            .api_url
+            .clone()
+            .or(profile.url.take())
+            .unwrap_or_else(|| TAIGA_API_URL.to_owned())
+            .trim_end_matches('/')
+            .to_owned();
+
+        Ok(Self {
+            api_url,
+            username: cli.username.clone().or(profile.username.take()),
+            password: cli.password.clone(),
+            project_name: cli.project_name.clone().or(profile.project.take()),
+            format: cli
+                .format
+                .clone()
+                .or(profile.format.take())
+                .unwrap_or_default(),
+            statuses: profile.statuses.take().unwrap_or_default(),
+            commits: profile.commits.take().unwrap_or_default(),
+            profile,
+        })
+    }
+
+    /// The project to work on.
+    ///
+    /// # Errors
+    /// Returns `ConfigError::NoProject` if neither the command line nor the profile names one.
+    pub fn project_name(&self) -> Result<&str, ConfigError> {
+        self.project_name.as_deref().ok_or(ConfigError::NoProject)
+    }
+
+    /// The password to log in with: the one given explicitly, else the profile's.
+    ///
+    /// The profile password is only resolved here, so that runs reusing a stored session
+    /// neither read `password_env` nor run `password_command`.
+    ///
+    /// # Errors
+    /// Returns `ConfigError` if `password_env` is unset or `password_command` fails.
+    pub fn login_password(&self) -> Result<Option<String>, ConfigError> {
+        match &self.password {
+            Some(password) => Ok(Some(password.clone())),
+            None => self.profile.password(),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/backlogr`, falling back to `~/.config/backlogr`.
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("backlogr"))
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use crate::{config::config_dir, integrations::taiga::AuthTokens};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CredentialsError {
+    #[error("Could not determine the credentials file location, set $HOME or $XDG_CONFIG_HOME")]
+    NoHomeDir,
+    #[error("Failed to access the credentials file")]
+    Io(#[from] io::Error),
+    #[error("Failed to parse the credentials file")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// A persisted login for a single Taiga instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub username: String,
+    #[serde(flatten)]
+    pub tokens: AuthTokens,
+}
+
+impl Session {
+    /// Whether the session may be used for `username`; any session will do without one.
+    pub fn belongs_to(&self, username: Option<&str>) -> bool {
+        username.is_none_or(|username| username == self.username)
+    }
+}
+
+/// Per-user store of Taiga sessions, keyed by the API URL of each instance.
+///
+/// Lives in `$XDG_CONFIG_HOME/backlogr/credentials.json` (or `~/.config/backlogr/credentials.json`).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Credentials {
+    #[serde(default)]
+    sessions: BTreeMap<String, Session>,
+}
+
+impl Credentials {
+    /// Location of the credentials file for the current user.
+    ///
+    /// # Errors
+    /// Returns `CredentialsError::NoHomeDir` if neither `$XDG_CONFIG_HOME` nor `$HOME` are set.
+    pub fn path() -> Result<PathBuf, CredentialsError> {
+        Ok(config_dir()
+            .ok_or(CredentialsError::NoHomeDir)?
+            .join("credentials.json"))
+    }
+
+    /// Loads the stored sessions, returning an empty store if the file does not exist yet.
+    ///
+    /// # Errors
+    /// Returns `CredentialsError` if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self, CredentialsError> {
+        let path = Self::path()?;
+
+        match fs::read_to_string(&path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the stored sessions back to disk, readable only by the current user.
+    ///
+    /// # Errors
+    /// Returns `CredentialsError` if the file cannot be written.
+    pub fn save(&self) -> Result<PathBuf, CredentialsError> {
+        let path = Self::path()?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(&path)?;
+        // The mode only applies to a new file, one written by an older version may be wider.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        serde_json::to_writer_pretty(&mut file, self)?;
+
+        Ok(path)
+    }
+
+    pub fn get(&self, api_url: &str) -> Option<&Session> {
+        self.sessions.get(api_url)
+    }
+
+    pub fn insert(&mut self, api_url: &str, session: Session) {
+        self.sessions.insert(api_url.to_owned(), session);
+    }
+
+    pub fn remove(&mut self, api_url: &str) -> Option<Session> {
+        self.sessions.remove(api_url)
+    }
+
+    /// Replaces the tokens of an existing session, e.g. after they were refreshed.
+    ///
+    /// # Errors
+    /// Returns `CredentialsError` if the updated store cannot be written.
+    pub fn update_tokens(api_url: &str, tokens: &AuthTokens) -> Result<(), CredentialsError> {
+        let mut credentials = Self::load()?;
+
+        if let Some(session) = credentials.sessions.get_mut(api_url) {
+            session.tokens = tokens.clone();
+            credentials.save()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use core::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A calendar date, stored as days since 1970-01-01.
+///
+/// Taiga exchanges dates as `YYYY-MM-DD` and timestamps as RFC 3339 strings; this covers
+/// what backlogr needs from both without pulling in a date library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date(i64);
+
+impl Date {
+    /// Current date in UTC.
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self(i64::try_from(secs / 86_400).unwrap_or_default())
+    }
+
+    pub fn from_ymd(year: i64, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Self(days_from_civil(year, month, day)))
+    }
+
+    /// Parses the date part of a `YYYY-MM-DD` or RFC 3339 string such as Taiga's `created_date`.
+    pub fn parse_prefix(s: &str) -> Option<Self> {
+        s.get(..10)?.parse().ok()
+    }
+
+    #[must_use]
+    pub fn add_days(self, days: i64) -> Self {
+        Self(self.0 + days)
+    }
+
+    /// Number of days from `other` to `self`.
+    pub fn days_since(self, other: Self) -> i64 {
+        self.0 - other.0
+    }
+
+    pub fn ymd(self) -> (i64, u32, u32) {
+        civil_from_days(self.0)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{s}' is not a valid date, expected YYYY-MM-DD");
+
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+
+        let year = year.parse().map_err(|_| invalid())?;
+        let month = month.parse().map_err(|_| invalid())?;
+        let day = day.parse().map_err(|_| invalid())?;
+
+        Self::from_ymd(year, month, day).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{year:04}-{month:02}-{day:02}")
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse_prefix(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid date '{s}'")))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+///
+/// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of [`days_from_civil`].
+///
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    (year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_reads_calendar_dates() {
+        let date: Date = "2025-07-18".parse().unwrap();
+
+        assert_eq!(date.ymd(), (2025, 7, 18));
+        assert_eq!(date.to_string(), "2025-07-18");
+        assert_eq!("1970-01-01".parse(), Ok(Date(0)));
+        assert_eq!(
+            "2024-02-29".parse::<Date>().map(Date::ymd),
+            Ok((2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_dates() {
+        for value in [
+            "2025-7-18",
+            "18-07-2025",
+            "2025-07-18T10:00:00Z",
+            "2025-13-01",
+            "2025-02-29",
+            "2025-04-31",
+            "2025-07-00",
+            "yesterday",
+            "",
+        ] {
+            assert_eq!(
+                value.parse::<Date>(),
+                Err(format!(
+                    "'{value}' is not a valid date, expected YYYY-MM-DD"
+                )),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_prefix_reads_timestamps() {
+        assert_eq!(
+            Date::parse_prefix("2025-07-18T10:00:00.000Z"),
+            Date::from_ymd(2025, 7, 18)
+        );
+        assert_eq!(Date::parse_prefix("2025-07"), None);
+    }
+}
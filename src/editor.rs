@@ -0,0 +1,143 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    env, fs,
+    io::{self, Write},
+    process,
+};
+
+/// Line that opens and closes the TOML front matter of a document.
+const DELIMITER: &str = "+++";
+
+#[derive(thiserror::Error, Debug)]
+pub enum EditorError {
+    #[error("Failed to access the temporary file")]
+    Io(#[from] io::Error),
+    #[error("Editor `{editor}` failed: {reason}")]
+    Failed { editor: String, reason: String },
+    #[error("The document must start with a front matter block between `+++` lines")]
+    MissingFrontMatter,
+    #[error("Invalid front matter")]
+    Parse(#[from] toml::de::Error),
+    #[error("Failed to write the front matter")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Lets the user edit `text` in `$VISUAL` or `$EDITOR` (`vi` if neither is set).
+///
+/// The text is written to a new temporary file named after `file_name`, so editors can pick the
+/// syntax from its extension, and read back once the editor exits.
+///
+/// # Errors
+/// Returns `EditorError::Failed` if the editor cannot be started or exits with an error, or
+/// `EditorError::Io` if the temporary file cannot be written or read.
+pub fn edit(text: &str, file_name: &str) -> Result<String, EditorError> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+
+    let path = env::temp_dir().join(format!("backlogr-{}-{file_name}", process::id()));
+
+    // The temporary directory is shared: refuse to reuse a file, or follow a link, planted
+    // there by someone else, and keep the story away from other users.
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(&path)?.write_all(text.as_bytes())?;
+
+    let failed = |reason: String| EditorError::Failed {
+        editor: editor.clone(),
+        reason,
+    };
+
+    // Run through the shell so EDITOR may carry arguments, e.g. `code --wait`.
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("backlogr")
+        .arg(&path)
+        .status();
+
+    let result = match status {
+        Ok(status) if status.success() => Ok(fs::read_to_string(&path)?),
+        Ok(status) => Err(failed(status.to_string())),
+        Err(err) => Err(failed(err.to_string())),
+    };
+
+    let _ = fs::remove_file(&path);
+
+    result
+}
+
+/// Writes `fields` as TOML front matter followed by `body`.
+///
+/// # Errors
+/// Returns `EditorError::Serialize` if `fields` cannot be written as TOML.
+pub fn to_document<T: Serialize>(fields: &T, body: &str) -> Result<String, EditorError> {
+    let front_matter = toml::to_string(fields)?;
+
+    Ok(format!(
+        "{DELIMITER}\n{front_matter}{DELIMITER}\n\n{body}\n"
+    ))
+}
+
+/// Splits a document written by [`to_document`] back into its fields and body.
+///
+/// Surrounding blank lines of the body are dropped.
+///
+/// # Errors
+/// Returns `EditorError::MissingFrontMatter` if the document does not start with a front
+/// matter block, or `EditorError::Parse` if the block is not valid.
+pub fn from_document<T: DeserializeOwned>(text: &str) -> Result<(T, String), EditorError> {
+    let rest = text
+        .trim_start()
+        .strip_prefix(DELIMITER)
+        .ok_or(EditorError::MissingFrontMatter)?;
+
+    let (front_matter, body) = rest
+        .split_once(&format!("\n{DELIMITER}"))
+        .ok_or(EditorError::MissingFrontMatter)?;
+
+    let fields = toml::from_str(front_matter)?;
+
+    Ok((fields, body.trim_matches('\n').trim_end().to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Fields {
+        subject: String,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn from_document_reads_back_to_document() {
+        let fields = Fields {
+            subject: "Login \"fails\"".to_owned(),
+            tags: vec!["auth".to_owned()],
+        };
+        let body = "Steps:\n\n1. Open `/login`\n\n+++ not a delimiter";
+
+        let document = to_document(&fields, body).unwrap();
+        let (read, read_body): (Fields, String) = from_document(&document).unwrap();
+
+        assert_eq!(read, fields);
+        assert_eq!(read_body, body);
+    }
+
+    #[test]
+    fn from_document_needs_front_matter() {
+        let result = from_document::<Fields>("subject = \"No delimiters\"\n");
+
+        assert!(matches!(result, Err(EditorError::MissingFrontMatter)));
+    }
+}
@@ -0,0 +1,318 @@
+use serde::Deserialize;
+use std::{collections::BTreeMap, io, process};
+
+#[derive(thiserror::Error, Debug)]
+pub enum GitError {
+    #[error("Failed to run git")]
+    Io(#[from] io::Error),
+    #[error("`git {args}` failed: {stderr}")]
+    Failed { args: String, stderr: String },
+}
+
+/// A commit message, with the SHA of its commit when known.
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub sha: Option<String>,
+    pub message: String,
+}
+
+impl Commit {
+    /// The first line of the message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default().trim()
+    }
+
+    /// The SHA shortened to the usual 7 characters.
+    pub fn short_sha(&self) -> Option<&str> {
+        self.sha
+            .as_deref()
+            .map(|sha| match sha.char_indices().nth(7) {
+                Some((end, _)) => &sha[..end],
+                None => sha,
+            })
+    }
+}
+
+/// Reads the commits selected by `revisions`, such as `["main..HEAD"]`, oldest first.
+///
+/// # Errors
+/// Returns `GitError` if git cannot be run or does not accept the revisions.
+pub fn commits(revisions: &[&str]) -> Result<Vec<Commit>, GitError> {
+    // Fields and records are separated by ASCII unit and record separators.
+    let mut args = vec!["log", "--reverse", "--format=%H%x1f%B%x1e"];
+    args.extend(revisions);
+
+    let log = run(&args)?;
+
+    Ok(log
+        .split('\x1e')
+        .filter_map(|record| {
+            let (sha, message) = record.trim_start_matches('\n').split_once('\x1f')?;
+            Some(Commit {
+                sha: Some(sha.to_owned()),
+                message: message.trim().to_owned(),
+            })
+        })
+        .collect())
+}
+
+/// Runs git with `args` in the current directory and returns its stdout.
+///
+/// # Errors
+/// Returns `GitError` if git cannot be run or exits with an error.
+pub fn run(args: &[&str]) -> Result<String, GitError> {
+    let output = process::Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(GitError::Failed {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Drops the comment lines git adds to the message file it hands to editors and hooks.
+///
+/// Refs such as `#12` at the start of a line are kept, even though git would strip them.
+pub fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .take_while(|line| !line.starts_with("# ------------------------ >8"))
+        .filter(|line| !(line.starts_with("# ") || *line == "#"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+/// How commit messages move the stories they mention.
+///
+/// ```toml
+/// [profiles.work.commits]
+/// url = "https://github.com/me/app/commit/{sha}"
+///
+/// [profiles.work.commits.rules]
+/// done = ["fix", "closes"]
+/// wip = ["wip", "start"]
+/// "Ready for test" = ["qa"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommitRules {
+    /// Link to a commit in the comments, with `{sha}` standing for its SHA.
+    pub url: Option<String>,
+    /// Keywords by target status: `new`, `wip`, `done`, or the name or slug of any status.
+    pub rules: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for CommitRules {
+    fn default() -> Self {
+        let keywords = |words: &[&str]| words.iter().map(|&word| word.to_owned()).collect();
+
+        Self {
+            url: None,
+            rules: BTreeMap::from([
+                (
+                    "done".to_owned(),
+                    keywords(&[
+                        "fix", "fixes", "fixed", "close", "closes", "closed", "resolve",
+                        "resolves", "resolved", "done", "complete", "finish",
+                    ]),
+                ),
+                (
+                    "wip".to_owned(),
+                    keywords(&["wip", "start", "starts", "progress", "feat", "feature"]),
+                ),
+            ]),
+        }
+    }
+}
+
+/// A story ref found in a commit message and the status it should move to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitAction {
+    pub reference: usize,
+    pub status: String,
+    pub keyword: String,
+}
+
+impl CommitRules {
+    fn status_of(&self, keyword: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| k.eq_ignore_ascii_case(keyword)))
+            .map(|(status, _)| status.as_str())
+    }
+
+    /// Finds the story refs of a message and the status each one moves to.
+    ///
+    /// A ref follows the keyword it applies to, alone or in a list: `closes #12`,
+    /// `fixes #3, #4 and #5`. Refs without a keyword of their own take the type of a
+    /// Conventional Commits subject, as in `fix: login bug (#42)`. Refs without any keyword
+    /// are ignored; each ref is moved once, by its first keyword.
+    pub fn actions(&self, message: &str) -> Vec<CommitAction> {
+        let conventional =
+            conventional_type(message).and_then(|kind| Some((kind, self.status_of(kind)?)));
+
+        let mut actions: Vec<CommitAction> = Vec::new();
+        let mut current: Option<(&str, &str)> = None;
+
+        for word in words(message) {
+            let action = if let Some(reference) = parse_ref(word) {
+                current
+                    .or(conventional)
+                    .map(|(keyword, status)| (reference, keyword, status))
+            } else {
+                current = match self.status_of(word) {
+                    Some(status) => Some((word, status)),
+                    None if ["and", "&", ""].contains(&word) => current,
+                    None => None,
+                };
+                None
+            };
+
+            if let Some((reference, keyword, status)) = action {
+                if !actions.iter().any(|a| a.reference == reference) {
+                    actions.push(CommitAction {
+                        reference,
+                        status: status.to_owned(),
+                        keyword: keyword.to_lowercase(),
+                    });
+                }
+            }
+        }
+
+        actions
+    }
+
+    /// Text of the comment left on the stories a commit moves.
+    pub fn comment(&self, commit: &Commit) -> String {
+        let subject = commit.subject();
+
+        match (commit.short_sha(), &commit.sha, &self.url) {
+            (Some(short), Some(sha), Some(url)) => {
+                format!(
+                    "Moved by commit [{short}]({}): {subject}",
+                    url.replace("{sha}", sha)
+                )
+            }
+            (Some(short), _, _) => format!("Moved by commit `{short}`: {subject}"),
+            _ => format!("Moved by commit: {subject}"),
+        }
+    }
+}
+
+/// Every story ref mentioned in a message, with or without a keyword, in order.
+pub fn mentioned_refs(message: &str) -> Vec<usize> {
+    let mut refs = Vec::new();
+
+    for word in words(message) {
+        if let Some(reference) = parse_ref(word) {
+            if !refs.contains(&reference) {
+                refs.push(reference);
+            }
+        }
+    }
+
+    refs
+}
+
+/// The words of a message, without surrounding punctuation.
+fn words(message: &str) -> impl Iterator<Item = &str> {
+    message
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| "()[],.;:!".contains(c)))
+}
+
+/// `12` from `#12`.
+fn parse_ref(word: &str) -> Option<usize> {
+    word.strip_prefix('#')?.parse().ok()
+}
+
+/// `fix` from a subject such as `fix(auth)!: login bug`.
+pub fn conventional_type(message: &str) -> Option<&str> {
+    let subject = message.lines().next()?;
+    let (prefix, _) = subject.split_once(':')?;
+    let kind = prefix
+        .split_once('(')
+        .map_or(prefix, |(kind, _)| kind)
+        .trim_end_matches('!');
+
+    (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .then_some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(reference: usize, status: &str, keyword: &str) -> CommitAction {
+        CommitAction {
+            reference,
+            status: status.to_owned(),
+            keyword: keyword.to_owned(),
+        }
+    }
+
+    #[test]
+    fn short_sha_keeps_seven_characters() {
+        let commit = |sha: &str| Commit {
+            sha: Some(sha.to_owned()),
+            message: String::new(),
+        };
+
+        assert_eq!(commit("0123456789abcdef").short_sha(), Some("0123456"));
+        assert_eq!(commit("abc").short_sha(), Some("abc"));
+        // Not a real SHA, but must not panic in the middle of a character.
+        assert_eq!(commit("ééééééééé").short_sha(), Some("ééééééé"));
+    }
+
+    #[test]
+    fn actions_apply_keywords_to_the_refs_after_them() {
+        let rules = CommitRules::default();
+
+        assert_eq!(
+            rules.actions("Closes #12"),
+            vec![action(12, "done", "closes")]
+        );
+        assert_eq!(
+            rules.actions("Start #3, fixes #4 and #5"),
+            vec![
+                action(3, "wip", "start"),
+                action(4, "done", "fixes"),
+                action(5, "done", "fixes"),
+            ]
+        );
+    }
+
+    #[test]
+    fn actions_fall_back_to_the_conventional_type() {
+        let rules = CommitRules::default();
+
+        assert_eq!(
+            rules.actions("fix(auth): login bug (#42)"),
+            vec![action(42, "done", "fix")]
+        );
+        assert_eq!(rules.actions("docs: mention #42"), vec![]);
+    }
+
+    #[test]
+    fn actions_move_each_ref_once() {
+        let rules = CommitRules::default();
+
+        assert_eq!(
+            rules.actions("wip #7\n\nfixes #7"),
+            vec![action(7, "wip", "wip")]
+        );
+    }
+
+    #[test]
+    fn strip_comments_keeps_refs_and_drops_the_diff() {
+        let message = "fix: login\n\n#12 was the cause\n# Please enter the commit message\n#\n\
+                       # ------------------------ >8 ------------------------\ndiff --git a/x b/x";
+
+        assert_eq!(strip_comments(message), "fix: login\n\n#12 was the cause");
+    }
+}
@@ -0,0 +1,136 @@
+use clap::ValueEnum;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::git::{self, Commit, GitError};
+
+/// Marks the hooks written by `hooks install`, so they can be replaced safely.
+const MARKER: &str = "# Installed by backlogr";
+
+/// SHA git uses for a missing side of a pushed ref, e.g. the remote side of a new branch.
+const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+
+#[derive(thiserror::Error, Debug)]
+pub enum HookError {
+    #[error(transparent)]
+    Git(#[from] GitError),
+    #[error("Failed to write hook {}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{} already exists and was not installed by backlogr, use --force to replace it", path.display())]
+    Exists { path: PathBuf },
+}
+
+/// What the hooks do when a commit mentions a story that is already closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ClosedPolicy {
+    /// Print a warning and let the commit or push through
+    Warn,
+    /// Reject the commit or push
+    Fail,
+}
+
+impl ClosedPolicy {
+    fn as_arg(self) -> &'static str {
+        match self {
+            ClosedPolicy::Warn => "warn",
+            ClosedPolicy::Fail => "fail",
+        }
+    }
+}
+
+/// Writes the `commit-msg` and `pre-push` hooks of the current repository.
+///
+/// Hooks go to the directory git uses, honouring `core.hooksPath`. Returns their paths.
+///
+/// # Errors
+/// Returns `HookError::Exists` if a hook not written by backlogr is in the way and `force` is
+/// not set, or `HookError::Io` / `HookError::Git` if the hooks cannot be written.
+pub fn install(closed: ClosedPolicy, force: bool) -> Result<Vec<PathBuf>, HookError> {
+    let dir = PathBuf::from(git::run(&["rev-parse", "--git-path", "hooks"])?.trim());
+    let closed = closed.as_arg();
+
+    let hooks = [
+        (
+            "commit-msg",
+            format!("exec backlogr hooks check --file \"$1\" --closed {closed}"),
+        ),
+        (
+            "pre-push",
+            format!("exec backlogr hooks check --pre-push --closed {closed}"),
+        ),
+    ];
+
+    let mut installed = Vec::new();
+    for (name, command) in hooks {
+        let path = dir.join(name);
+        write_hook(&path, &format!("#!/bin/sh\n{MARKER}\n{command}\n"), force)?;
+        installed.push(path);
+    }
+
+    Ok(installed)
+}
+
+fn write_hook(path: &Path, script: &str, force: bool) -> Result<(), HookError> {
+    let io_error = |source| HookError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    match fs::read_to_string(path) {
+        Ok(existing) if !force && !existing.contains(MARKER) => {
+            return Err(HookError::Exists {
+                path: path.to_path_buf(),
+            })
+        }
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(io_error(err)),
+        _ => {}
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    fs::write(path, script).map_err(io_error)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(io_error)?;
+    }
+
+    Ok(())
+}
+
+/// The commits a push sends, from the `<local ref> <local sha> <remote ref> <remote sha>`
+/// lines git gives the `pre-push` hook on stdin.
+///
+/// For a new branch, these are the commits not on any remote branch yet.
+///
+/// # Errors
+/// Returns `GitError` if the commits cannot be listed.
+pub fn pushed_commits(lines: &str) -> Result<Vec<Commit>, GitError> {
+    let mut commits = Vec::new();
+
+    for line in lines.lines() {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let [_, local_sha, _, remote_sha] = fields[..] else {
+            continue;
+        };
+
+        if local_sha == ZERO_SHA {
+            // Deleting a remote branch pushes no commits.
+            continue;
+        }
+
+        let pushed = if remote_sha == ZERO_SHA {
+            git::commits(&[local_sha, "--not", "--remotes"])?
+        } else {
+            git::commits(&[&format!("{remote_sha}..{local_sha}")])?
+        };
+
+        commits.extend(pushed);
+    }
+
+    Ok(commits)
+}
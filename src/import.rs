@@ -0,0 +1,101 @@
+use serde::de::DeserializeOwned;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::integrations::taiga::{BacklogStory, NewStory};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImportError {
+    #[error("Failed to read {}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Invalid CSV in {}", path.display())]
+    Csv { path: PathBuf, source: csv::Error },
+    #[error("Invalid JSON in {}", path.display())]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Invalid YAML in {}", path.display())]
+    Yaml {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    #[error("The id '{id}' is used by several stories of {}", path.display())]
+    DuplicateId { path: PathBuf, id: String },
+    #[error("Unknown file type {}, expected a .csv, .json, .yaml or .yml file", path.display())]
+    UnknownFormat { path: PathBuf },
+}
+
+/// Reads the stories of an import file, picking the format from its extension.
+///
+/// CSV files have a header row naming the columns; JSON and YAML files hold a list of
+/// objects with the same field names.
+///
+/// # Errors
+/// Returns `ImportError::UnknownFormat` for other extensions, or the error of the format if
+/// the file cannot be read or parsed.
+pub fn read_stories(path: &Path) -> Result<Vec<NewStory>, ImportError> {
+    read_list(path)
+}
+
+/// Reads the stories of a backlog file, in the same formats as [`read_stories`].
+///
+/// # Errors
+/// Returns `ImportError::DuplicateId` if two stories share an id, or the errors of
+/// [`read_stories`].
+pub fn read_backlog(path: &Path) -> Result<Vec<BacklogStory>, ImportError> {
+    let stories: Vec<BacklogStory> = read_list(path)?;
+
+    for (index, story) in stories.iter().enumerate() {
+        if stories[..index]
+            .iter()
+            .any(|other| other.id.eq_ignore_ascii_case(&story.id))
+        {
+            return Err(ImportError::DuplicateId {
+                path: path.to_path_buf(),
+                id: story.id.clone(),
+            });
+        }
+    }
+
+    Ok(stories)
+}
+
+fn read_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, ImportError> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+
+    let content = |path: &Path| {
+        fs::read_to_string(path).map_err(|source| ImportError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+
+    match extension.as_deref() {
+        Some("csv") => csv::Reader::from_reader(content(path)?.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|source| ImportError::Csv {
+                path: path.to_path_buf(),
+                source,
+            }),
+        Some("json") => serde_json::from_str(&content(path)?).map_err(|source| ImportError::Json {
+            path: path.to_path_buf(),
+            source,
+        }),
+        Some("yaml" | "yml") => {
+            serde_yaml::from_str(&content(path)?).map_err(|source| ImportError::Yaml {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+        _ => Err(ImportError::UnknownFormat {
+            path: path.to_path_buf(),
+        }),
+    }
+}
@@ -1,15 +1,55 @@
 use clap::ValueEnum;
 use core::fmt;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+};
 
 use color_eyre::owo_colors::OwoColorize;
 use eyre::Result;
-use serde_json::json;
+use serde_json::{json, Value};
+
+use crate::{cache::RefCache, progress, ExitOnError};
+
+mod batch;
+mod bulk;
+mod change;
+mod changelog;
+mod epics;
+mod filters;
+mod issues;
+mod listing;
+mod plan;
+mod sprints;
+mod stories;
+mod tasks;
+
+pub use batch::{BatchReport, Selection};
+pub use bulk::{ImportReport, NewStory};
+pub use change::{Change, ChangeAction};
+pub use changelog::{Changelog, ChangelogStyle, GroupBy};
+pub use epics::{Epic, EpicProgress, Epics};
+pub use filters::StoryFilter;
+pub use issues::{Issue, IssueChanges, IssueDetail, IssueType, Issues, Priority, Severity};
+pub use listing::{Column, GroupView, ListingView, SortKey, StoryView};
+pub use plan::{BacklogStory, Plan};
+pub use sprints::{Sprint, SprintDetail, Sprints};
+pub use stories::{idempotency_tag, StoryChanges, StoryDetail, StoryFields, StoryStatus};
+pub use tasks::{Task, Tasks};
+
+use stories::StoryEpic;
+
+/// REST API root of the hosted Taiga instance, used when no other URL is configured.
+pub const TAIGA_API_URL: &str = "https://api.taiga.io/api/v1";
 
-use crate::ExitOnError;
+/// How many times an update is retried on top of a newer version of the item.
+const MAX_CONFLICT_RETRIES: usize = 3;
 
-pub const TAIGA_API_URL: &str = "https://api.taiga.io/api/v1";
+/// Above this many refs, stories are picked from one listing of the project rather than
+/// looked up one by one.
+const MAX_REF_LOOKUPS: usize = 5;
 
 #[derive(thiserror::Error, Debug)]
 pub enum TaigaAPIError {
@@ -17,8 +57,18 @@ pub enum TaigaAPIError {
     Authentication(String),
     #[error("User story not found: {0}")]
     StoryNotFound(String),
+    #[error("Task not found: {0}")]
+    TaskNotFound(String),
+    #[error("Issue not found: {0}")]
+    IssueNotFound(String),
+    #[error("Epic not found: {0}")]
+    EpicNotFound(String),
     #[error("Project not found: {0}")]
     ProjectNotFound(String),
+    #[error("Invalid value: {0}")]
+    ValueNotFound(String),
+    #[error("Version conflict: {0}")]
+    VersionConflict(String),
     #[error("API error: {0}")]
     ApiError(String),
     #[error("Network error: {0}")]
@@ -37,6 +87,8 @@ impl TaigaAPIError {
                 eprintln!("     export PASSWORD=your_taiga_password");
                 eprintln!("   • Verify credentials by logging into Taiga web interface");
                 eprintln!("   • Check if your account is active and not locked");
+                eprintln!("   • Or store a session once with: backlogr login");
+                eprintln!("   • Self-hosted instance? Set TAIGA_URL=https://your.taiga/api/v1");
             }
             TaigaAPIError::StoryNotFound(story) => {
                 eprintln!("💡 Story '{}' not found. Try:", story);
@@ -45,6 +97,24 @@ impl TaigaAPIError {
                 eprintln!("   • Check for typos in the story title");
                 eprintln!("   • Ensure you're in the correct project");
             }
+            TaigaAPIError::TaskNotFound(task) => {
+                eprintln!("💡 Task '{}' not found. Try:", task);
+                eprintln!("   • backlogr task list      # See all available tasks");
+                eprintln!("   • Check that you used the task ref, not the story ref");
+                eprintln!("   • Ensure you're in the correct project");
+            }
+            TaigaAPIError::IssueNotFound(issue) => {
+                eprintln!("💡 Issue '{}' not found. Try:", issue);
+                eprintln!("   • backlogr issue list     # See all available issues");
+                eprintln!("   • Check that you used the issue ref, not a story ref");
+                eprintln!("   • Ensure you're in the correct project");
+            }
+            TaigaAPIError::EpicNotFound(epic) => {
+                eprintln!("💡 Epic '{}' not found. Try:", epic);
+                eprintln!("   • backlogr epic list      # See all available epics");
+                eprintln!("   • Check that you used the epic ref, not a story ref");
+                eprintln!("   • Ensure you're in the correct project");
+            }
             TaigaAPIError::ProjectNotFound(project) => {
                 eprintln!("💡 Project '{}' not found. Check:", project);
                 eprintln!("   • Project name spelling (case-sensitive)");
@@ -52,6 +122,19 @@ impl TaigaAPIError {
                 eprintln!("   • If the project exists in your Taiga instance");
                 eprintln!("   • Set correct PROJECT_NAME environment variable");
             }
+            TaigaAPIError::ValueNotFound(_) => {
+                eprintln!("💡 The value does not exist in this project. Check:");
+                eprintln!("   • The available values listed above");
+                eprintln!("   • Spelling (names are matched case-insensitively)");
+                eprintln!("   • The project settings in the Taiga web interface");
+                eprintln!("   • Custom workflow? Map your statuses in [profiles.<name>.statuses]");
+            }
+            TaigaAPIError::VersionConflict(_) => {
+                eprintln!("💡 Someone else changed the same fields at the same time:");
+                eprintln!("   • backlogr show <REF>     # See the current state of the story");
+                eprintln!("   • Run the command again to apply your change on top of theirs");
+                eprintln!("   • Avoid running concurrent jobs that update the same items");
+            }
             TaigaAPIError::ApiError(msg) => {
                 eprintln!("💡 API error occurred:");
                 if msg.contains("500") || msg.contains("502") || msg.contains("503") {
@@ -106,9 +189,14 @@ impl TaigaAPIError {
             TaigaAPIError::Authentication(_) => 1,
             TaigaAPIError::InternalError(_) => 1,
             TaigaAPIError::StoryNotFound(_) => 2,
+            TaigaAPIError::TaskNotFound(_) => 2,
+            TaigaAPIError::IssueNotFound(_) => 2,
+            TaigaAPIError::EpicNotFound(_) => 2,
             TaigaAPIError::ProjectNotFound(_) => 3,
             TaigaAPIError::ApiError(_) => 4,
             TaigaAPIError::DeserializationError(_) => 5,
+            TaigaAPIError::ValueNotFound(_) => 6,
+            TaigaAPIError::VersionConflict(_) => 7,
         }
     }
 
@@ -119,28 +207,51 @@ impl TaigaAPIError {
     }
 }
 
+/// Callback invoked with the new tokens whenever the session is refreshed.
+type RefreshHook = Box<dyn Fn(&AuthTokens)>;
+
 pub struct TaigaAPI {
-    pub auth_token: String,
     pub api_url: String,
+    tokens: RefCell<AuthTokens>,
+    on_refresh: Option<RefreshHook>,
+    statuses: StatusMapping,
+    ref_cache: RefCell<RefCache>,
+    /// Items fetched while resolving a ref, used once by `retrieve_current_version` to save
+    /// a request.
+    prefetched: RefCell<HashMap<(&'static str, usize), ItemVersion>>,
+    /// Story IDs taken from the ref cache in this run, with their project and ref, so that a
+    /// stale one can be looked up again.
+    cached_refs: RefCell<HashMap<usize, (usize, usize)>>,
+    /// New IDs of the stories whose cached ID turned out to be stale.
+    moved: RefCell<HashMap<usize, usize>>,
 }
 
 impl TaigaAPI {
     /// Authenticates a user with the Taiga API using a username and password.
     ///
+    /// `api_url` is the root of the REST API of the Taiga instance, e.g.
+    /// [`TAIGA_API_URL`] or `https://taiga.example.com/api/v1` for self-hosted instances.
+    ///
     /// On success, returns a new instance of the API client with a valid auth token.
     ///
     /// # Errors
     /// Returns `TaigaAPIError::ApiError` if the credentials are invalid
     /// or if there is a problem communicating with the API.
-    pub fn authenticate(username: &str, password: &str) -> Result<Self, TaigaAPIError> {
-        eprintln!("🔐 Authenticating with Taiga API...");
+    pub fn authenticate(
+        api_url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, TaigaAPIError> {
+        let api_url = api_url.trim_end_matches('/').to_owned();
+
+        progress!("🔐 Authenticating with Taiga API at {api_url}...");
         let payload = json!({
             "type": "normal",
             "username" : username,
             "password" : password
         });
 
-        let response = minreq::post(format!("{TAIGA_API_URL}/auth"))
+        let response = minreq::post(format!("{api_url}/auth"))
             .with_header("Content-Type", "application/json")
             .with_json(&payload)?
             .send()?;
@@ -154,12 +265,109 @@ impl TaigaAPI {
         }
 
         let user_auth_detail: UserAuthenticationDetail = response.json()?;
-        let auth_token = user_auth_detail.auth_token;
 
-        Ok(Self {
-            auth_token,
-            api_url: TAIGA_API_URL.to_owned(),
-        })
+        Ok(Self::from_tokens(
+            &api_url,
+            AuthTokens {
+                auth_token: user_auth_detail.auth_token,
+                refresh: user_auth_detail.refresh,
+            },
+        ))
+    }
+
+    /// Builds an API client from previously obtained tokens, e.g. the ones stored by `backlogr login`.
+    ///
+    /// No request is made; an expired auth token is refreshed on the first request that gets a 401.
+    pub fn from_tokens(api_url: &str, tokens: AuthTokens) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_owned(),
+            tokens: RefCell::new(tokens),
+            on_refresh: None,
+            statuses: StatusMapping::default(),
+            ref_cache: RefCell::default(),
+            prefetched: RefCell::default(),
+            cached_refs: RefCell::default(),
+            moved: RefCell::default(),
+        }
+    }
+
+    /// Sets which project statuses stand for `new`, `wip` and `done`.
+    #[must_use]
+    pub fn with_status_mapping(mut self, statuses: StatusMapping) -> Self {
+        self.statuses = statuses;
+        self
+    }
+
+    /// Remembers the IDs of the stories looked up by ref in `cache`.
+    #[must_use]
+    pub fn with_ref_cache(mut self, cache: RefCache) -> Self {
+        self.ref_cache = RefCell::new(cache);
+        self
+    }
+
+    /// Registers a callback that receives the new tokens every time the session is refreshed.
+    #[must_use]
+    pub fn on_refresh(mut self, hook: impl Fn(&AuthTokens) + 'static) -> Self {
+        self.on_refresh = Some(Box::new(hook));
+        self
+    }
+
+    /// Returns the tokens of the current session.
+    pub fn tokens(&self) -> AuthTokens {
+        self.tokens.borrow().clone()
+    }
+
+    /// Exchanges the refresh token for a new pair of tokens through `/auth/refresh`.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::Authentication` if the refresh token is no longer valid.
+    fn refresh(&self) -> Result<(), TaigaAPIError> {
+        progress!("🔐 Session expired, refreshing auth token...");
+
+        let payload = json!({ "refresh": self.tokens.borrow().refresh });
+
+        let response = minreq::post(format!("{}/auth/refresh", self.api_url))
+            .with_header("Content-Type", "application/json")
+            .with_json(&payload)?
+            .send()?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::Authentication(format!(
+                "Could not refresh the session, please run `backlogr login` again. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        let tokens: AuthTokens = response.json()?;
+
+        if let Some(hook) = &self.on_refresh {
+            hook(&tokens);
+        }
+
+        *self.tokens.borrow_mut() = tokens;
+
+        Ok(())
+    }
+
+    /// Sends an authenticated request, refreshing the session and retrying once on a 401.
+    fn send(&self, request: minreq::Request) -> Result<minreq::Response, TaigaAPIError> {
+        let authorized = |request: minreq::Request| {
+            request.with_header(
+                "Authorization",
+                format!("Bearer {}", self.tokens.borrow().auth_token),
+            )
+        };
+
+        let response = authorized(request.clone()).send()?;
+
+        if response.status_code != 401 {
+            return Ok(response);
+        }
+
+        self.refresh()?;
+
+        Ok(authorized(request).send()?)
     }
 
     /// Lists all user stories for the given project ID.
@@ -169,13 +377,59 @@ impl TaigaAPI {
     /// # Errors
     /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
     pub fn list_all_stories(&self, project_id: usize) -> Result<Vec<UserStory>, TaigaAPIError> {
-        let mut all_stories = Vec::new();
+        let mut stories = self.list_all(Resource::UserStory, &format!("project={project_id}"))?;
+        self.categorize(&mut stories);
+
+        Ok(stories)
+    }
+
+    /// Sets the `Status` each story stands for, according to the [`StatusMapping`].
+    fn categorize(&self, stories: &mut [UserStory]) {
+        for story in stories {
+            story.category = self.statuses.categorize(&story.status_extra_info);
+        }
+    }
+
+    /// Sets the position of the status of each story on the project board.
+    fn set_status_order(
+        &self,
+        project_id: usize,
+        stories: &mut [UserStory],
+    ) -> Result<(), TaigaAPIError> {
+        let statuses = self.list_statuses(project_id, Resource::UserStory)?;
+
+        for story in stories {
+            story.status_order = statuses
+                .iter()
+                .find(|status| status.id == story.status)
+                .map_or(i64::MAX, |status| status.order);
+        }
+
+        Ok(())
+    }
+
+    /// Fetches every page of the listing endpoint of `resource`, e.g. `/userstories?{query}`.
+    fn list_all<T: DeserializeOwned>(
+        &self,
+        resource: Resource,
+        query: &str,
+    ) -> Result<Vec<T>, TaigaAPIError> {
+        self.list_all_from(resource.path(), query)
+    }
+
+    /// Fetches every page of a listing endpoint, e.g. `/milestones?{query}`.
+    fn list_all_from<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &str,
+    ) -> Result<Vec<T>, TaigaAPIError> {
+        let mut all_items = Vec::new();
         let mut page = 1;
         let page_size = 100;
 
         loop {
-            let (stories, has_more) = self.list_stories_page(project_id, page, page_size)?;
-            all_stories.extend(stories);
+            let (items, has_more) = self.list_page(path, query, page, page_size)?;
+            all_items.extend(items);
 
             if !has_more {
                 break;
@@ -184,33 +438,38 @@ impl TaigaAPI {
             page += 1;
         }
 
-        Ok(all_stories)
+        Ok(all_items)
     }
 
-    fn list_stories_page(
+    fn list_page<T: DeserializeOwned>(
         &self,
-        project_id: usize,
+        path: &str,
+        query: &str,
         page: usize,
         page_size: usize,
-    ) -> Result<(Vec<UserStory>, bool), TaigaAPIError> {
-        let auth_token = self.auth_token.clone();
+    ) -> Result<(Vec<T>, bool), TaigaAPIError> {
         let api_url = self.api_url.clone();
 
-        let response = minreq::get(format!(
-            "{api_url}/userstories?project={project_id}&page={page}&page_size={page_size}"
-        ))
-        .with_header("Authorization", format!("Bearer {auth_token}"))
-        .send()?;
+        let request = query_params(query)
+            .into_iter()
+            .fold(
+                minreq::get(format!("{api_url}/{path}")),
+                |request, (key, value)| request.with_param(key, value),
+            )
+            .with_param("page", page.to_string())
+            .with_param("page_size", page_size.to_string());
+
+        let response = self.send(request)?;
 
         if response.status_code != 200 {
             let body = response.as_str()?;
             return Err(TaigaAPIError::ApiError(format!(
-                "Fetching the list of stories failed. HTTP {}: {}",
+                "Fetching the list of {path} failed. HTTP {}: {}",
                 response.status_code, body
             )));
         }
 
-        let stories: Vec<UserStory> = response.json()?;
+        let items: Vec<T> = response.json()?;
 
         let current_page_count = response
             .headers
@@ -232,7 +491,7 @@ impl TaigaAPI {
 
         let has_more = is_paginated && current_page_count == page_size_header;
 
-        Ok((stories, has_more))
+        Ok((items, has_more))
     }
 
     /// Retrieves the project ID for a given project name where the current user is a member.
@@ -242,9 +501,7 @@ impl TaigaAPI {
     /// or if the project name is not found among the user’s projects.
     pub fn get_project_id(&self, project_name: &str) -> Result<usize, TaigaAPIError> {
         let user_id = {
-            let response = minreq::get(format!("{TAIGA_API_URL}/users/me"))
-                .with_header("Authorization", format!("Bearer {}", self.auth_token))
-                .send()?;
+            let response = self.send(minreq::get(format!("{}/users/me", self.api_url)))?;
 
             if response.status_code != 200 {
                 let body = response.as_str()?;
@@ -257,12 +514,13 @@ impl TaigaAPI {
             user_detail.id
         };
 
-        eprintln!("🔗 Connected to Taiga (User ID: {})", user_id.bold().cyan());
+        progress!("🔗 Connected to Taiga (User ID: {})", user_id.bold().cyan());
 
         let Some(project_id) = ({
-            let response = minreq::get(format!("{TAIGA_API_URL}/projects?member={user_id}"))
-                .with_header("Authorization", format!("Bearer {}", self.auth_token))
-                .send()?;
+            let response = self.send(minreq::get(format!(
+                "{}/projects?member={user_id}",
+                self.api_url
+            )))?;
 
             if response.status_code != 200 {
                 let body = response.as_str()?;
@@ -284,7 +542,7 @@ impl TaigaAPI {
             )));
         };
 
-        println!(
+        progress!(
             "📂 Project: {} (ID: {})",
             project_name.bright_green().bold(),
             project_id.bright_green().bold()
@@ -293,7 +551,10 @@ impl TaigaAPI {
         Ok(project_id)
     }
 
-    /// Creates a new user story in the specified project with the given subject and status.
+    /// Creates a new user story in the specified project with the given subject, status and
+    /// tags.
+    ///
+    /// Returns the change describing the new story.
     ///
     /// # Errors
     /// Returns `TaigaAPIError::ApiError` if the request fails, status cannot be found,
@@ -304,55 +565,250 @@ impl TaigaAPI {
         subject: &str,
         description: &str,
         status: &Status,
-    ) -> Result<usize, TaigaAPIError> {
-        let auth_token = self.auth_token.clone();
-
-        let status_id = self.get_status_id(project_id, status)?;
+        tags: &[String],
+    ) -> Result<Change, TaigaAPIError> {
+        let status_id = self.get_status_id(project_id, Resource::UserStory, status)?;
 
         let payload = json!({
             "project": project_id,
             "subject": subject,
             "description": description,
-            "status": status_id
+            "status": status_id,
+            "tags": tags
         });
 
-        let response = minreq::post(format!("{TAIGA_API_URL}/userstories"))
-            .with_headers([
-                ("Authorization", format!("Bearer {auth_token}")),
-                ("Content-Type", "application/json".to_owned()),
-            ])
-            .with_json(&payload)?
-            .send()?;
+        self.create(Resource::UserStory, &payload)
+    }
+
+    /// Creates an item from `payload` and describes it as a change.
+    fn create(&self, resource: Resource, payload: &Value) -> Result<Change, TaigaAPIError> {
+        let fields = self.post_item(resource, payload)?;
+
+        Ok(self.change(resource, ChangeAction::Created, None, Some(&fields)))
+    }
+
+    /// Creates an item from `payload` and returns its ID, reference number and version.
+    fn create_item(
+        &self,
+        resource: Resource,
+        payload: &Value,
+    ) -> Result<ItemDetail, TaigaAPIError> {
+        Ok(serde_json::from_value(self.post_item(resource, payload)?)?)
+    }
+
+    /// Creates an item from `payload` and returns all its fields.
+    fn post_item(&self, resource: Resource, payload: &Value) -> Result<Value, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+        let path = resource.path();
+
+        let response = self.send(
+            minreq::post(format!("{api_url}/{path}"))
+                .with_header("Content-Type", "application/json")
+                .with_json(payload)?,
+        )?;
 
         if response.status_code != 201 {
             let body = response.as_str()?;
             return Err(TaigaAPIError::ApiError(format!(
-                "Creating new story failed. HTTP {}: {}",
+                "Creating new {resource} failed. HTTP {}: {}",
                 response.status_code, body
             )));
         }
 
-        let story_detail: UserStoryDetail = response.json()?;
-
-        Ok(story_detail.reference)
+        Ok(response.json()?)
     }
 
     /// Finds the internal user story ID from a reference number within a given project.
     ///
+    /// IDs found through `/userstories/by_ref` are remembered in the ref cache and trusted
+    /// without a request. If Taiga later answers 404 for a cached ID, the entry is dropped and
+    /// the ref looked up again, see [`Self::relocate`].
+    ///
     /// # Errors
-    /// Returns `TaigaAPIError::ApiError` if the story reference is not found.
+    /// Returns `TaigaAPIError::StoryNotFound` if no story has this ref, or
+    /// `TaigaAPIError::ApiError` if the lookup fails.
     pub fn get_story_id(&self, project_id: usize, story_id: usize) -> Result<usize, TaigaAPIError> {
-        eprintln!("🔍 Looking up user story with ref #{story_id} in project...");
+        let cached = self
+            .ref_cache
+            .borrow()
+            .story_id(&self.api_url, project_id, story_id);
+        if let Some(id) = cached {
+            self.cached_refs
+                .borrow_mut()
+                .insert(id, (project_id, story_id));
+            return Ok(id);
+        }
 
-        let user_story_list = self.list_all_stories(project_id)?;
+        progress!("🔍 Looking up user story with ref #{story_id} in project...");
 
-        user_story_list
-            .iter()
-            .find(|v| v.reference == story_id)
-            .map(|v| v.id)
+        let id = self
+            .find_by_ref(Resource::UserStory, project_id, story_id)?
             .ok_or(TaigaAPIError::StoryNotFound(format!(
                 "User story with ref #{story_id} not found."
-            )))
+            )))?;
+
+        let mut cache = self.ref_cache.borrow_mut();
+        cache.insert_story(&self.api_url, project_id, story_id, id);
+        cache.persist();
+
+        Ok(id)
+    }
+
+    /// Looks up again the ref of a story whose cached ID Taiga no longer knows.
+    ///
+    /// Returns the fields of the story under its new ID, or `None` if `item_id` did not come
+    /// from the ref cache, in which case the 404 stands.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::StoryNotFound` if the ref no longer exists either.
+    fn relocate(&self, resource: Resource, item_id: usize) -> Result<Option<Value>, TaigaAPIError> {
+        if !matches!(resource, Resource::UserStory) {
+            return Ok(None);
+        }
+        let Some((project_id, reference)) = self.cached_refs.borrow_mut().remove(&item_id) else {
+            return Ok(None);
+        };
+
+        progress!("🔍 Cached ID of user story #{reference} is stale, looking it up again...");
+
+        self.ref_cache
+            .borrow_mut()
+            .remove_story(&self.api_url, item_id);
+        let id = self.get_story_id(project_id, reference)?;
+        self.moved.borrow_mut().insert(item_id, id);
+
+        let fields = self
+            .prefetched
+            .borrow_mut()
+            .remove(&(resource.path(), id))
+            .map(|item| item.fields);
+
+        match fields {
+            Some(fields) => Ok(Some(fields)),
+            None => self.get(resource, id).map(Some),
+        }
+    }
+
+    /// URL of an item, following the stories whose cached ID was stale.
+    fn item_url(&self, resource: Resource, item_id: usize) -> String {
+        let item_id = match resource {
+            Resource::UserStory => self
+                .moved
+                .borrow()
+                .get(&item_id)
+                .copied()
+                .unwrap_or(item_id),
+            _ => item_id,
+        };
+
+        format!("{}/{}/{item_id}", self.api_url, resource.path())
+    }
+
+    /// Reads the user stories with the given refs, leaving out the refs that match none.
+    ///
+    /// A few refs are looked up one by one through [`Self::get_story_id`], more are picked
+    /// from a single listing of the project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if a request fails or the API response is invalid.
+    fn stories_by_ref(
+        &self,
+        project_id: usize,
+        refs: &[usize],
+    ) -> Result<Vec<Value>, TaigaAPIError> {
+        if refs.len() > MAX_REF_LOOKUPS {
+            let refs: HashSet<u64> = refs.iter().map(|reference| *reference as u64).collect();
+            let items: Vec<Value> =
+                self.list_all(Resource::UserStory, &format!("project={project_id}"))?;
+
+            return Ok(items
+                .into_iter()
+                .filter(|item| item["ref"].as_u64().is_some_and(|r| refs.contains(&r)))
+                .collect());
+        }
+
+        let mut items = Vec::new();
+        for reference in refs {
+            let id = match self.get_story_id(project_id, *reference) {
+                Ok(id) => id,
+                Err(TaigaAPIError::StoryNotFound(_)) => continue,
+                Err(err) => return Err(err),
+            };
+
+            // Looking the ref up already read the story, unless its ID was cached.
+            items.push(
+                self.retrieve_current_version(Resource::UserStory, id)?
+                    .fields,
+            );
+        }
+
+        Ok(items)
+    }
+
+    /// Looks an item up by its reference number with the `by_ref` endpoint of its resource,
+    /// instead of listing the whole project.
+    ///
+    /// Returns `None` if the project has no such item.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    fn find_by_ref(
+        &self,
+        resource: Resource,
+        project_id: usize,
+        reference: usize,
+    ) -> Result<Option<usize>, TaigaAPIError> {
+        let path = format!(
+            "{}/by_ref?ref={reference}&project={project_id}",
+            resource.path()
+        );
+
+        self.prefetch(resource, &path)
+    }
+
+    /// Reads a single item from `path`, keeping its fields for the next
+    /// [`Self::retrieve_current_version`] of the item.
+    ///
+    /// Returns the ID of the item, or `None` if Taiga answers 404.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    fn prefetch(&self, resource: Resource, path: &str) -> Result<Option<usize>, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let response = self.send(minreq::get(format!("{api_url}/{path}")))?;
+
+        if response.status_code == 404 {
+            return Ok(None);
+        }
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Looking up the {resource} failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        let fields: Value = response.json()?;
+
+        self.remember(resource, fields).map(Some)
+    }
+
+    /// Keeps an item that was just read as the base of its next update, so that
+    /// [`Self::retrieve_current_version`] does not fetch it again. Returns its ID.
+    fn remember(&self, resource: Resource, fields: Value) -> Result<usize, TaigaAPIError> {
+        let detail: ItemDetail = serde_json::from_value(fields.clone())?;
+
+        self.prefetched.borrow_mut().insert(
+            (resource.path(), detail.id),
+            ItemVersion {
+                version: detail.version,
+                fields,
+            },
+        );
+
+        Ok(detail.id)
     }
 
     /// Updates the status of an existing user story in the specified project.
@@ -368,111 +824,460 @@ impl TaigaAPI {
         story_id: usize,
         user_story_id: usize,
         status: &Status,
-    ) -> Result<(), TaigaAPIError> {
-        let auth_token = self.auth_token.clone();
-        let api_url = self.api_url.clone();
+    ) -> Result<Change, TaigaAPIError> {
+        progress!("🔍 Fetching '{status}' status ID for the project...");
+        let status = self.category_status(project_id, Resource::UserStory, status)?;
 
-        eprintln!("✅ Found user story ID: {}", user_story_id.bold().cyan());
-        eprintln!("🔍 Fetching '{status}' status ID for the project...");
+        self.update_status(Resource::UserStory, story_id, user_story_id, status)
+    }
 
-        let status_id = self.get_status_id(project_id, status)?;
+    /// Moves a user story to any status of the project, given by its name or slug.
+    ///
+    /// `wip`, `done` and `new` are accepted as aliases of the statuses they are mapped to.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound`, listing the statuses of the project, if the
+    /// status does not exist, or `TaigaAPIError::ApiError` if the story cannot be updated.
+    pub fn move_story(
+        &self,
+        project_id: usize,
+        story_id: usize,
+        user_story_id: usize,
+        status: &str,
+    ) -> Result<Change, TaigaAPIError> {
+        progress!("🔍 Fetching '{status}' status ID for the project...");
+        let status = self.resolve_status(project_id, Resource::UserStory, status)?;
 
-        eprintln!("✅ '{status}' status ID is: {}", status_id.bold().green());
+        self.update_status(Resource::UserStory, story_id, user_story_id, status)
+    }
 
-        eprintln!("🔍 Retrieving current version of user story #{story_id}...");
+    fn update_status(
+        &self,
+        resource: Resource,
+        reference: usize,
+        item_id: usize,
+        status: StatusDetail,
+    ) -> Result<Change, TaigaAPIError> {
+        let StatusDetail {
+            id: status_id,
+            name: status,
+            ..
+        } = status;
+
+        progress!("✅ Found {resource} ID: {}", item_id.bold().cyan());
+        progress!("✅ '{status}' status ID is: {}", status_id.bold().green());
+
+        progress!("🔄 Updating {resource} #{reference} status to '{status}'...");
+
+        self.update(resource, item_id, json!({ "status": status_id }))
+    }
 
-        let user_story_current_version = self.retrieve_current_version(user_story_id)?;
+    /// Deletes a user story with the given internal ID from the Taiga project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the deletion fails.
+    pub fn delete_story(&self, story_id: usize) -> Result<Change, TaigaAPIError> {
+        let change = self.delete(Resource::UserStory, story_id)?;
 
-        eprintln!("✅ Current version of user story #{story_id} is {user_story_current_version}");
+        let mut cache = self.ref_cache.borrow_mut();
+        cache.remove_story(&self.api_url, change.id);
+        cache.persist();
 
-        eprintln!("🔄 Updating user story status to '{status}'...");
+        Ok(change)
+    }
 
-        let payload = json!({
-            "status": status_id,
-            "version": user_story_current_version
-        });
+    /// Deletes an item and describes it as it was before.
+    fn delete(&self, resource: Resource, item_id: usize) -> Result<Change, TaigaAPIError> {
+        let current = self.retrieve_current_version(resource, item_id)?;
 
-        let response = minreq::patch(format!("{api_url}/userstories/{user_story_id}"))
-            .with_headers([
-                ("Authorization", format!("Bearer {auth_token}")),
-                ("Content-Type", "application/json".to_owned()),
-            ])
-            .with_json(&payload)?
-            .send()?;
+        let response = self.send(minreq::delete(self.item_url(resource, item_id)))?;
 
-        if response.status_code != 200 {
+        if response.status_code != 204 {
             let body = response.as_str()?;
             return Err(TaigaAPIError::ApiError(format!(
-                "Failed to update {story_id} to '{status}'. HTTP {}: {}",
+                "Failed to delete the {resource}. HTTP {}: {}",
                 response.status_code, body
             )));
         }
 
-        eprintln!(
-            "✅ Successfully updated user story  {story_id} to '{status}' (version {user_story_current_version})"
-        );
+        Ok(self.change(resource, ChangeAction::Deleted, Some(&current.fields), None))
+    }
 
-        Ok(())
+    /// Applies `changes` to an item, passing its current version as Taiga requires.
+    ///
+    /// Returns the change, with the version of the item after the update.
+    fn update(
+        &self,
+        resource: Resource,
+        item_id: usize,
+        changes: Value,
+    ) -> Result<Change, TaigaAPIError> {
+        let current = self.retrieve_current_version(resource, item_id)?;
+
+        self.update_from(resource, item_id, changes, current)
     }
 
-    /// Deletes a user story with the given internal ID from the Taiga project.
+    /// Applies `changes` to an item as it was read in `base`.
+    ///
+    /// When someone else updates the item in between, Taiga rejects the stale version. The item
+    /// is then read again and, if none of the fields in `changes` were touched by the other
+    /// update, `changes` are applied on top of the new version, up to `MAX_CONFLICT_RETRIES`
+    /// times.
     ///
     /// # Errors
-    /// Returns `TaigaAPIError::ApiError` if the deletion fails.
-    pub fn delete_story(&self, story_id: usize) -> Result<(), TaigaAPIError> {
-        let auth_token = self.auth_token.clone();
+    /// Returns `TaigaAPIError::VersionConflict` if the other update changed the same fields or
+    /// the item keeps changing, or `TaigaAPIError::ApiError` if a request fails.
+    fn update_from(
+        &self,
+        resource: Resource,
+        item_id: usize,
+        changes: Value,
+        mut base: ItemVersion,
+    ) -> Result<Change, TaigaAPIError> {
+        for _ in 0..=MAX_CONFLICT_RETRIES {
+            if let Some(fields) = self.patch(resource, item_id, &changes, base.version)? {
+                return Ok(self.change(
+                    resource,
+                    ChangeAction::Updated,
+                    Some(&base.fields),
+                    Some(&fields),
+                ));
+            }
 
-        let response = minreq::delete(format!("{TAIGA_API_URL}/userstories/{story_id}"))
-            .with_header("Authorization", format!("Bearer {auth_token}"))
-            .send()?;
+            let current = self.retrieve_current_version(resource, item_id)?;
 
-        if response.status_code != 204 {
+            if let Some(field) = clashing_field(&changes, &base, &current) {
+                return Err(TaigaAPIError::VersionConflict(format!(
+                    "'{field}' of {resource} #{} was changed by someone else (now at version {})",
+                    current.reference(),
+                    current.version
+                )));
+            }
+
+            progress!(
+                "🔁 The {resource} #{} was updated meanwhile, retrying on version {}...",
+                current.reference(),
+                current.version
+            );
+            base = current;
+        }
+
+        Err(TaigaAPIError::VersionConflict(format!(
+            "The {resource} #{} kept changing, gave up after {MAX_CONFLICT_RETRIES} retries",
+            base.reference()
+        )))
+    }
+
+    /// Sends `changes` for the given version of an item.
+    ///
+    /// Returns the fields of the item at its new version, or `None` if Taiga rejected
+    /// `version` as stale.
+    fn patch(
+        &self,
+        resource: Resource,
+        item_id: usize,
+        changes: &Value,
+        version: usize,
+    ) -> Result<Option<Value>, TaigaAPIError> {
+        let mut payload = changes.clone();
+        payload["version"] = version.into();
+
+        let response = self.send(
+            minreq::patch(self.item_url(resource, item_id))
+                .with_header("Content-Type", "application/json")
+                .with_json(&payload)?,
+        )?;
+
+        if response.status_code != 200 {
             let body = response.as_str()?;
+
+            // Taiga answers a stale version with `{"version": "The version doesn't match ..."}`.
+            if matches!(response.status_code, 400 | 409) && is_version_error(body) {
+                return Ok(None);
+            }
+
             return Err(TaigaAPIError::ApiError(format!(
-                "Failed to delete the story failed. HTTP {}: {}",
+                "Failed to update the {resource}. HTTP {}: {}",
                 response.status_code, body
             )));
         }
 
-        Ok(())
+        Ok(Some(response.json()?))
+    }
+
+    /// Fetches a single item by its internal ID.
+    fn get<T: DeserializeOwned>(
+        &self,
+        resource: Resource,
+        item_id: usize,
+    ) -> Result<T, TaigaAPIError> {
+        let response = self.send(minreq::get(self.item_url(resource, item_id)))?;
+
+        if response.status_code == 404 {
+            if let Some(fields) = self.relocate(resource, item_id)? {
+                return Ok(serde_json::from_value(fields)?);
+            }
+        }
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Fetching the {resource} failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        Ok(response.json()?)
     }
 
-    /// Retrieves the current version number of the specified user story.
+    /// Fetches any other endpoint, given as a path relative to the API root with its query.
+    fn fetch<T: DeserializeOwned>(&self, path: &str) -> Result<T, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let response = self.send(minreq::get(format!("{api_url}/{path}")))?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Unable to retrieve {path}. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        Ok(response.json()?)
+    }
+
+    /// Resolves a per-project value such as an issue type or a priority by its name.
     ///
-    /// This is required when updating a story to avoid version conflicts.
+    /// Names are compared case-insensitively.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound`, listing the valid names, if there is no match.
+    fn resolve_named(
+        &self,
+        project_id: usize,
+        path: &str,
+        label: &str,
+        name: &str,
+    ) -> Result<usize, TaigaAPIError> {
+        let values = self.list_named(project_id, path)?;
+
+        values
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(name))
+            .map(|v| v.id)
+            .ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(format!(
+                    "No {label} named '{name}'. Available: {}",
+                    values
+                        .iter()
+                        .map(|v| v.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })
+    }
+
+    /// Finds the user ID of a project member by username or full name, compared
+    /// case-insensitively.
     ///
     /// # Errors
-    /// Returns `TaigaAPIError::ApiError` if the story details cannot be fetched.
-    fn retrieve_current_version(&self, user_story_id: usize) -> Result<usize, TaigaAPIError> {
+    /// Returns `TaigaAPIError::ValueNotFound`, listing the members of the project, if none matches.
+    fn get_member_id(&self, project_id: usize, name: &str) -> Result<usize, TaigaAPIError> {
+        let members = self.list_members(project_id)?;
+
+        members
+            .iter()
+            .find(|m| {
+                m.username.eq_ignore_ascii_case(name)
+                    || m.full_name_display.eq_ignore_ascii_case(name)
+            })
+            .map(|m| m.id)
+            .ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(format!(
+                    "No member named '{name}'. Available: {}",
+                    members
+                        .iter()
+                        .map(|m| m.username.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })
+    }
+
+    /// Lists the users that are members of the project.
+    fn list_members(&self, project_id: usize) -> Result<Vec<Member>, TaigaAPIError> {
         let api_url = self.api_url.clone();
-        let auth_token = self.auth_token.clone();
 
-        let response = minreq::get(format!("{api_url}/userstories/{user_story_id}"))
-            .with_header("Authorization", format!("Bearer {auth_token}"))
-            .send()?;
+        let response = self.send(minreq::get(format!("{api_url}/users?project={project_id}")))?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Unable to retrieve the members of {project_id}. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        Ok(response.json()?)
+    }
+
+    /// Lists the values of a per-project endpoint such as `/priorities`, in project order.
+    fn list_named(&self, project_id: usize, path: &str) -> Result<Vec<NamedValue>, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let response = self.send(minreq::get(format!(
+            "{api_url}/{path}?project={project_id}"
+        )))?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Unable to retrieve {path} for {project_id}. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        let mut values: Vec<NamedValue> = response.json()?;
+        values.sort_by_key(|v| v.order);
+
+        Ok(values)
+    }
+
+    /// Retrieves the current version number of the specified item, along with its fields.
+    ///
+    /// This is required when updating an item to avoid version conflicts; the fields tell a
+    /// later conflict apart from one that can be retried.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the item details cannot be fetched.
+    fn retrieve_current_version(
+        &self,
+        resource: Resource,
+        item_id: usize,
+    ) -> Result<ItemVersion, TaigaAPIError> {
+        let prefetched = self
+            .prefetched
+            .borrow_mut()
+            .remove(&(resource.path(), item_id));
+        if let Some(item) = prefetched {
+            return Ok(item);
+        }
 
-        let user_story_detail: UserStoryDetail = response.json()?;
+        let fields: Value = self.get(resource, item_id)?;
+        let detail: ItemDetail = serde_json::from_value(fields.clone())?;
 
-        Ok(user_story_detail.version)
+        Ok(ItemVersion {
+            version: detail.version,
+            fields,
+        })
     }
 
     /// Fetches the status ID corresponding to a `Status` enum variant for a given project.
     ///
     /// # Errors
-    /// Returns `TaigaAPIError::ApiError` if the status cannot be found or the request fails.
-    fn get_status_id(&self, project_id: usize, status: &Status) -> Result<usize, TaigaAPIError> {
-        let auth_token = self.auth_token.clone();
-        let api_url = self.api_url.clone();
+    /// Returns `TaigaAPIError::ValueNotFound` if the project has no such status, or
+    /// `TaigaAPIError::ApiError` if the request fails.
+    fn get_status_id(
+        &self,
+        project_id: usize,
+        resource: Resource,
+        status: &Status,
+    ) -> Result<usize, TaigaAPIError> {
+        Ok(self.category_status(project_id, resource, status)?.id)
+    }
 
-        let status = match status {
-            Status::Done => "Done",
-            Status::Wip => "In progress",
-            Status::New => "New",
-        };
+    /// Finds the project status a `Status` stands for.
+    ///
+    /// The names configured in the [`StatusMapping`] are tried first, then the Taiga defaults,
+    /// and finally Taiga's own flags: the first closed status for `Done` and the first open one
+    /// for `New`.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound`, listing the statuses of the project, if there
+    /// is no match.
+    fn category_status(
+        &self,
+        project_id: usize,
+        resource: Resource,
+        status: &Status,
+    ) -> Result<StatusDetail, TaigaAPIError> {
+        let statuses = self.list_statuses(project_id, resource)?;
+
+        self.find_category_status(resource, &statuses, status)
+            .ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(format!(
+                    "No {resource} status stands for '{status}'. Available: {}",
+                    status_names(&statuses)
+                ))
+            })
+    }
 
-        let response = minreq::get(format!("{api_url}/userstory-statuses?project={project_id}"))
-            .with_header("Authorization", format!("Bearer {auth_token}"))
-            .send()?;
+    fn find_category_status(
+        &self,
+        resource: Resource,
+        statuses: &[StatusDetail],
+        status: &Status,
+    ) -> Option<StatusDetail> {
+        let default_name = resource.status_name(status);
+
+        self.statuses
+            .names(status)
+            .iter()
+            .map(String::as_str)
+            .chain([default_name])
+            .find_map(|name| find_status(statuses, name))
+            .or_else(|| match status {
+                Status::Done => statuses.iter().find(|v| v.is_closed),
+                Status::New => statuses.iter().find(|v| !v.is_closed),
+                Status::Wip => None,
+            })
+            .cloned()
+    }
+
+    /// Finds a status of the resource by its name or slug, compared case-insensitively.
+    ///
+    /// When nothing matches, the names of the `Status` variants (`new`, `wip`, `done`) are
+    /// accepted as aliases of the statuses they are mapped to.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound`, listing the statuses of the project, if there
+    /// is no match.
+    fn resolve_status(
+        &self,
+        project_id: usize,
+        resource: Resource,
+        name: &str,
+    ) -> Result<StatusDetail, TaigaAPIError> {
+        let statuses = self.list_statuses(project_id, resource)?;
+
+        find_status(&statuses, name)
+            .cloned()
+            .or_else(|| {
+                Status::from_str(name, true)
+                    .ok()
+                    .and_then(|alias| self.find_category_status(resource, &statuses, &alias))
+            })
+            .ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(format!(
+                    "No {resource} status named '{name}'. Available: {}",
+                    status_names(&statuses)
+                ))
+            })
+    }
+
+    /// Lists the statuses available for the resource in a project, in board order.
+    fn list_statuses(
+        &self,
+        project_id: usize,
+        resource: Resource,
+    ) -> Result<Vec<StatusDetail>, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+        let statuses_path = resource.statuses_path();
+
+        let response = self.send(minreq::get(format!(
+            "{api_url}/{statuses_path}?project={project_id}"
+        )))?;
 
         if response.status_code != 200 {
             let body = response.as_str()?;
@@ -482,19 +1287,150 @@ impl TaigaAPI {
             )));
         }
 
-        let statuses_list: Vec<UserStoryStatusDetail> = response.json()?;
+        let mut statuses: Vec<StatusDetail> = response.json()?;
+        statuses.sort_by_key(|v| v.order);
 
-        statuses_list
-            .iter()
-            .find(|v| v.name == status)
-            .map(|v| v.id)
-            .ok_or(TaigaAPIError::ApiError(format!(
-                "Could not find '{status}' status for project"
-            )))
+        Ok(statuses)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, ValueEnum)]
+/// Splits a query string such as `project=1&tags=a%26b` into its pairs, with the values
+/// percent-decoded.
+///
+/// minreq encodes the parameters it is given, and would encode the `%` of a value that is
+/// already encoded, so queries are handed to it as pairs rather than in the URL.
+fn query_params(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value =
+                urlencoding::decode(value).map_or_else(|_| value.to_owned(), Cow::into_owned);
+            (key.to_owned(), value)
+        })
+        .collect()
+}
+
+/// The first field of `changes` that was modified between `base` and `current` to a value
+/// other than the one in `changes`.
+///
+/// Map-valued fields such as `points` only carry the keys being set, so they are compared key
+/// by key and a clash is reported as e.g. `points.3`.
+/// Whether an error body of Taiga is about the `version` sent, rather than mentioning the word.
+fn is_version_error(body: &str) -> bool {
+    serde_json::from_str::<Value>(body).is_ok_and(|body| body.get("version").is_some())
+}
+
+fn clashing_field(changes: &Value, base: &ItemVersion, current: &ItemVersion) -> Option<String> {
+    clashing_key(changes, &base.fields, &current.fields)
+}
+
+fn clashing_key(changes: &Value, before: &Value, now: &Value) -> Option<String> {
+    changes.as_object()?.iter().find_map(|(key, value)| {
+        let before = before.get(key);
+        let now = now.get(key);
+
+        if value.is_object() {
+            let nested = clashing_key(
+                value,
+                before.unwrap_or(&Value::Null),
+                now.unwrap_or(&Value::Null),
+            );
+            return nested.map(|nested| format!("{key}.{nested}"));
+        }
+
+        (before != now && now != Some(value)).then(|| key.clone())
+    })
+}
+
+fn find_status<'a>(statuses: &'a [StatusDetail], name: &str) -> Option<&'a StatusDetail> {
+    statuses
+        .iter()
+        .find(|v| v.name.eq_ignore_ascii_case(name) || v.slug.eq_ignore_ascii_case(name))
+}
+
+fn status_names(statuses: &[StatusDetail]) -> String {
+    statuses
+        .iter()
+        .map(|v| v.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Kinds of Taiga items that are addressed by a project-wide ref and carry their own statuses.
+#[derive(Debug, Clone, Copy)]
+enum Resource {
+    UserStory,
+    Task,
+    Issue,
+    Epic,
+}
+
+impl Resource {
+    /// Endpoint of the resource, relative to the API root.
+    fn path(self) -> &'static str {
+        match self {
+            Resource::UserStory => "userstories",
+            Resource::Task => "tasks",
+            Resource::Issue => "issues",
+            Resource::Epic => "epics",
+        }
+    }
+
+    /// Name of the resource in the results printed by `--format`.
+    fn kind(self) -> &'static str {
+        match self {
+            Resource::UserStory => "user_story",
+            Resource::Task => "task",
+            Resource::Issue => "issue",
+            Resource::Epic => "epic",
+        }
+    }
+
+    /// Segment of the web interface URLs of the resource.
+    fn web_path(self) -> &'static str {
+        match self {
+            Resource::UserStory => "us",
+            Resource::Task => "task",
+            Resource::Issue => "issue",
+            Resource::Epic => "epic",
+        }
+    }
+
+    /// Endpoint listing the statuses available for the resource in a project.
+    fn statuses_path(self) -> &'static str {
+        match self {
+            Resource::UserStory => "userstory-statuses",
+            Resource::Task => "task-statuses",
+            Resource::Issue => "issue-statuses",
+            Resource::Epic => "epic-statuses",
+        }
+    }
+
+    /// Name Taiga gives by default to each `Status` of the resource.
+    fn status_name(self, status: &Status) -> &'static str {
+        match (self, status) {
+            (_, Status::New) => "New",
+            (_, Status::Wip) => "In progress",
+            (Resource::UserStory | Resource::Epic, Status::Done) => "Done",
+            (Resource::Task | Resource::Issue, Status::Done) => "Closed",
+        }
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::UserStory => write!(f, "user story"),
+            Resource::Task => write!(f, "task"),
+            Resource::Issue => write!(f, "issue"),
+            Resource::Epic => write!(f, "epic"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ValueEnum)]
 pub enum Status {
     Done,
     Wip,
@@ -523,11 +1459,76 @@ impl std::fmt::Display for Status {
     }
 }
 
+/// Names of the project statuses that stand for each `Status`, compared case-insensitively.
+///
+/// Statuses that are not listed anywhere still count as `done` when Taiga flags them as closed.
+///
+/// ```toml
+/// [profiles.es.statuses]
+/// new = ["Nuevo"]
+/// wip = ["En curso"]
+/// done = ["Hecho"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusMapping {
+    pub new: Vec<String>,
+    pub wip: Vec<String>,
+    pub done: Vec<String>,
+}
+
+impl Default for StatusMapping {
+    fn default() -> Self {
+        Self {
+            new: vec!["New".to_owned()],
+            wip: vec!["In progress".to_owned(), "WIP".to_owned()],
+            done: vec!["Done".to_owned(), "Ready".to_owned()],
+        }
+    }
+}
+
+impl StatusMapping {
+    fn names(&self, status: &Status) -> &[String] {
+        match status {
+            Status::New => &self.new,
+            Status::Wip => &self.wip,
+            Status::Done => &self.done,
+        }
+    }
+
+    fn categorize(&self, info: &StatusInfo) -> Option<Status> {
+        Status::all()
+            .into_iter()
+            .find(|status| {
+                self.names(status)
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&info.name))
+            })
+            .or_else(|| info.is_closed.then_some(Status::Done))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+/// Any per-project value with a name and an order, e.g.
+/// <https://docs.taiga.io/api.html#object-priority-detail>
+struct NamedValue {
+    id: usize,
+    name: String,
+    #[serde(default)]
+    order: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// <https://docs.taiga.io/api.html#object-userstory-status-detail>
-struct UserStoryStatusDetail {
+struct StatusDetail {
     id: usize,
     name: String,
+    #[serde(default)]
+    slug: String,
+    #[serde(default)]
+    order: i64,
+    #[serde(default)]
+    is_closed: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -539,65 +1540,104 @@ pub struct UserStory {
     subject: String,
     status: usize,
     created_date: String,
+    #[serde(default)]
+    modified_date: String,
     status_extra_info: StatusInfo,
+    #[serde(default)]
+    assigned_to: Option<usize>,
+    #[serde(default)]
+    assigned_to_extra_info: Option<UserInfo>,
+    #[serde(default)]
+    tags: Vec<(String, Option<String>)>,
+    #[serde(default)]
+    total_points: Option<f64>,
+    #[serde(default)]
+    milestone_name: Option<String>,
+    #[serde(default)]
+    backlog_order: i64,
+    /// Taiga sends `null` rather than an empty list when the story has no epic.
+    #[serde(default)]
+    epics: Option<Vec<StoryEpic>>,
+    /// What the status stands for, set by [`TaigaAPI::categorize`].
+    #[serde(skip)]
+    category: Option<Status>,
+    /// Position of the status on the board, set by [`TaigaAPI::set_status_order`].
+    #[serde(skip)]
+    status_order: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct StatusInfo {
-    color: String,
-    is_closed: bool,
-    name: String,
+/// <https://docs.taiga.io/api.html#object-user-basic-info>
+struct UserInfo {
+    username: String,
+    #[serde(default)]
+    full_name_display: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusInfo {
+    pub color: String,
+    pub is_closed: bool,
+    pub name: String,
 }
 
 impl fmt::Display for UserStory {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let id = match self.status_extra_info.name.as_str() {
-            "Done" => self.reference.bright_green().bold().to_string(),
-            "In progress" => self.reference.bright_yellow().bold().to_string(),
-            "New" => self.reference.bright_blue().bold().to_string(),
-            _ => self.reference.bright_white().bold().to_string(),
+        let id = match self.category {
+            Some(Status::Done) => self.reference.bright_green().bold().to_string(),
+            Some(Status::Wip) => self.reference.bright_yellow().bold().to_string(),
+            Some(Status::New) => self.reference.bright_blue().bold().to_string(),
+            None => self.reference.bright_white().bold().to_string(),
         };
 
         write!(f, "#{:>2} {:<40}", id, self.subject)
     }
 }
 
+/// User stories grouped by status, in the order of the project board.
 pub struct UserStories {
-    pub new: Vec<UserStory>,
-    pub wip: Vec<UserStory>,
-    pub done: Vec<UserStory>,
-    pub other: HashMap<String, Vec<UserStory>>,
+    groups: Vec<StoryGroup>,
+    columns: Vec<Column>,
+}
+
+struct StoryGroup {
+    status: String,
+    category: Option<Status>,
+    stories: Vec<UserStory>,
 }
 
 impl UserStories {
+    /// Groups the stories by status, keeping their order within each group.
     pub fn new(stories: Vec<UserStory>) -> Self {
-        let mut new = Vec::new();
-        let mut wip = Vec::new();
-        let mut done = Vec::new();
-        let mut other: HashMap<String, Vec<UserStory>> = HashMap::new();
+        let mut groups: BTreeMap<(i64, usize), StoryGroup> = BTreeMap::new();
 
         for story in stories {
-            match story.status_extra_info.name.as_str() {
-                "New" => new.push(story),
-                "In progress" | "WIP" => wip.push(story),
-                "Done" | "Ready" => done.push(story),
-                status => other.entry(status.to_string()).or_default().push(story),
-            }
+            groups
+                .entry((story.status_order, story.status))
+                .or_insert_with(|| StoryGroup {
+                    status: story.status_extra_info.name.clone(),
+                    category: story.category.clone(),
+                    stories: Vec::new(),
+                })
+                .stories
+                .push(story);
         }
 
         Self {
-            new,
-            wip,
-            done,
-            other,
+            groups: groups.into_values().collect(),
+            columns: Vec::new(),
         }
     }
 
+    /// Sets the extra fields shown after the subject of each story.
+    #[must_use]
+    pub fn with_columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
     pub fn total_count(&self) -> usize {
-        self.new.len()
-            + self.wip.len()
-            + self.done.len()
-            + self.other.values().map(|v| v.len()).sum::<usize>()
+        self.groups.iter().map(|group| group.stories.len()).sum()
     }
 }
 
@@ -605,53 +1645,57 @@ impl fmt::Display for UserStories {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "📋 Total user stories: ({})\n", self.total_count())?;
 
-        if !self.new.is_empty() {
-            writeln!(f, "🆕 New ({})", self.new.len())?;
-            for story in &self.new {
-                writeln!(f, "  {story}")?;
-            }
-            writeln!(f)?;
-        }
-
-        if !self.wip.is_empty() {
-            writeln!(f, "🔄 Work in Progress ({})", self.wip.len())?;
-            for story in &self.wip {
-                writeln!(f, "  {story}")?;
+        for group in &self.groups {
+            let icon = match group.category {
+                Some(Status::New) => "🆕",
+                Some(Status::Wip) => "🔄",
+                Some(Status::Done) => "✅",
+                None => "📌",
+            };
+
+            writeln!(f, "{icon} {} ({})", group.status, group.stories.len())?;
+            for story in &group.stories {
+                writeln!(f, "  {}", story.row(&self.columns))?;
             }
             writeln!(f)?;
         }
 
-        if !self.done.is_empty() {
-            writeln!(f, "✅ Done ({})", self.done.len())?;
-            for story in &self.done {
-                writeln!(f, "  {story}")?;
-            }
-            writeln!(f)?;
-        }
+        Ok(())
+    }
+}
 
-        for (status, stories) in &self.other {
-            writeln!(f, "📌 {} ({})", status, stories.len())?;
-            for story in stories {
-                writeln!(f, "  {story}")?;
-            }
-            writeln!(f)?;
-        }
+/// An item as it was at a given version, with all the fields Taiga returned.
+#[derive(Debug, Clone, Default)]
+struct ItemVersion {
+    version: usize,
+    fields: Value,
+}
 
-        Ok(())
+impl ItemVersion {
+    fn reference(&self) -> &Value {
+        &self.fields["ref"]
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+/// Fields shared by the detail of every [`Resource`], e.g.
 /// <https://docs.taiga.io/api.html#object-userstory-detail-get>
-struct UserStoryDetail {
+struct ItemDetail {
     id: usize,
     #[serde(rename = "ref")]
     reference: usize,
     version: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-auth-refresh>
+pub struct AuthTokens {
+    pub auth_token: String,
+    pub refresh: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-/// <https://docs.taiga.io/api.html#object-userstory-status-detail>
+/// <https://docs.taiga.io/api.html#object-auth-user-detail>
 struct UserAuthenticationDetail {
     auth_token: String,
     email: String,
@@ -661,19 +1705,18 @@ struct UserAuthenticationDetail {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-enum Roles {
-    Front,
-    UX,
-    Back,
-    Design,
-    #[serde(rename = "Product Owner")]
-    ProductOwner,
+struct UserDetail {
+    id: usize,
+    username: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct UserDetail {
+/// <https://docs.taiga.io/api.html#object-user-contact-detail>
+struct Member {
     id: usize,
     username: String,
+    #[serde(default)]
+    full_name_display: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -688,3 +1731,123 @@ impl<T> ExitOnError<T> for Result<T, TaigaAPIError> {
         self.unwrap_or_else(|err| err.exit_with_tips())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn query_params_decode_values_once() {
+        assert_eq!(
+            query_params("project=7&tags=a%26b,c%23&created_date__gte=2025-07-01"),
+            vec![
+                ("project".to_owned(), "7".to_owned()),
+                ("tags".to_owned(), "a&b,c#".to_owned()),
+                ("created_date__gte".to_owned(), "2025-07-01".to_owned()),
+            ]
+        );
+        assert_eq!(query_params(""), vec![]);
+    }
+
+    fn version(version: usize, fields: Value) -> ItemVersion {
+        ItemVersion { version, fields }
+    }
+
+    #[test]
+    fn is_version_error_looks_for_the_version_key() {
+        assert!(is_version_error(
+            r#"{"version": "The version doesn't match with the current one"}"#
+        ));
+        assert!(!is_version_error(
+            r#"{"subject": "Mention the version in the subject"}"#
+        ));
+        assert!(!is_version_error("version mismatch"));
+    }
+
+    #[test]
+    fn clashing_field_ignores_fields_left_alone() {
+        let base = version(1, json!({"subject": "Old", "description": "A"}));
+        let current = version(2, json!({"subject": "Old", "description": "B"}));
+
+        assert_eq!(
+            clashing_field(&json!({"subject": "New"}), &base, &current),
+            None
+        );
+    }
+
+    #[test]
+    fn clashing_field_reports_fields_changed_by_both() {
+        let base = version(1, json!({"subject": "Old"}));
+        let current = version(2, json!({"subject": "Theirs"}));
+
+        assert_eq!(
+            clashing_field(&json!({"subject": "Mine"}), &base, &current),
+            Some("subject".to_owned())
+        );
+        // Both updates agree, there is nothing to lose.
+        assert_eq!(
+            clashing_field(&json!({"subject": "Theirs"}), &base, &current),
+            None
+        );
+    }
+
+    #[test]
+    fn clashing_field_compares_maps_key_by_key() {
+        let base = version(1, json!({"points": {"3": 1, "4": 1}}));
+        let current = version(2, json!({"points": {"3": 2, "4": 1}}));
+
+        assert_eq!(
+            clashing_field(&json!({"points": {"4": 5}}), &base, &current),
+            None
+        );
+        assert_eq!(
+            clashing_field(&json!({"points": {"3": 5}}), &base, &current),
+            Some("points.3".to_owned())
+        );
+    }
+
+    fn status_info(name: &str, is_closed: bool) -> StatusInfo {
+        StatusInfo {
+            color: "#999".to_owned(),
+            is_closed,
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn categorize_matches_names_ignoring_case() {
+        let statuses = StatusMapping {
+            wip: vec!["En curso".to_owned()],
+            ..StatusMapping::default()
+        };
+
+        assert_eq!(
+            statuses.categorize(&status_info("EN CURSO", false)),
+            Some(Status::Wip)
+        );
+        assert_eq!(
+            statuses.categorize(&status_info("new", false)),
+            Some(Status::New)
+        );
+        // Only the configured names count, not the defaults they replace.
+        assert_eq!(statuses.categorize(&status_info("WIP", false)), None);
+    }
+
+    #[test]
+    fn categorize_takes_unmapped_closed_statuses_as_done() {
+        let statuses = StatusMapping::default();
+
+        assert_eq!(
+            statuses.categorize(&status_info("Archived", true)),
+            Some(Status::Done)
+        );
+        assert_eq!(statuses.categorize(&status_info("Blocked", false)), None);
+        // A mapped name wins over the closed flag.
+        assert_eq!(
+            statuses.categorize(&status_info("In progress", true)),
+            Some(Status::Wip)
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use core::fmt;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{
+    Change, ChangeAction, Resource, Status, StoryFilter, TaigaAPI, TaigaAPIError, UserStory,
+};
+use crate::progress;
+
+/// User stories picked by ref or by a filter, with the refs that matched no story.
+#[derive(Debug)]
+pub struct Selection {
+    stories: Vec<UserStory>,
+    missing: Vec<usize>,
+}
+
+/// Outcome of a change applied to several stories, one entry per story.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct BatchReport {
+    entries: Vec<BatchEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchEntry {
+    #[serde(rename = "ref")]
+    reference: usize,
+    id: Option<usize>,
+    subject: Option<String>,
+    result: BatchResult,
+    version: Option<usize>,
+    old_status: Option<String>,
+    new_status: Option<String>,
+    url: Option<String>,
+    error: Option<String>,
+    /// Exit code of the error, if any.
+    #[serde(skip)]
+    exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BatchResult {
+    Moved,
+    /// The story was already in the target status.
+    Unchanged,
+    Deleted,
+    NotFound,
+    Failed,
+}
+
+impl TaigaAPI {
+    /// Finds the stories with the given refs and those matching `filter`, in ref order.
+    ///
+    /// A few refs are looked up one by one, more are picked from one listing of the project;
+    /// a filter is read with one listing too. The versions of the stories are kept so that
+    /// changing them needs no further request.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if a value of the filter does not exist, or
+    /// `TaigaAPIError::ApiError` if a request fails.
+    pub fn select_stories(
+        &self,
+        project_id: usize,
+        refs: &[usize],
+        filter: Option<&StoryFilter>,
+    ) -> Result<Selection, TaigaAPIError> {
+        let mut items = self.stories_by_ref(project_id, refs)?;
+
+        if let Some(filter) = filter {
+            let query = self.story_query(project_id, filter)?;
+            let matching: Vec<Value> = self.list_all(Resource::UserStory, &query)?;
+
+            for item in matching {
+                if !items.iter().any(|selected| selected["id"] == item["id"]) {
+                    items.push(item);
+                }
+            }
+        }
+
+        let mut stories = Vec::new();
+        for item in items {
+            let story: UserStory = serde_json::from_value(item.clone())?;
+
+            self.ref_cache.borrow_mut().insert_story(
+                &self.api_url,
+                project_id,
+                story.reference,
+                story.id,
+            );
+            self.remember(Resource::UserStory, item)?;
+
+            stories.push(story);
+        }
+        self.ref_cache.borrow().persist();
+
+        stories.sort_by_key(|story| story.reference);
+
+        let found: HashSet<usize> = stories.iter().map(|story| story.reference).collect();
+        let missing = refs
+            .iter()
+            .filter(|reference| !found.contains(reference))
+            .copied()
+            .collect();
+
+        Ok(Selection { stories, missing })
+    }
+
+    /// Moves every selected story to the status a `Status` stands for.
+    ///
+    /// Failures are reported per story rather than returned.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if the project has no such status.
+    pub fn set_stories_status(
+        &self,
+        project_id: usize,
+        selection: &Selection,
+        status: &Status,
+    ) -> Result<BatchReport, TaigaAPIError> {
+        let target = self.category_status(project_id, Resource::UserStory, status)?;
+
+        progress!(
+            "🔄 Moving {} user stories to '{}'...",
+            selection.stories.len(),
+            target.name
+        );
+
+        let mut report = BatchReport::new(selection);
+        for story in &selection.stories {
+            let mut entry = BatchEntry::new(story.reference, Some(story.subject.clone()));
+            entry.id = Some(story.id);
+            entry.old_status = Some(story.status_extra_info.name.clone());
+            entry.new_status = Some(target.name.clone());
+
+            if story.status == target.id {
+                entry.result = BatchResult::Unchanged;
+
+                // Read from the listing of the selection, this makes no request.
+                if let Ok(current) = self.retrieve_current_version(Resource::UserStory, story.id) {
+                    entry.record(self.change(
+                        Resource::UserStory,
+                        ChangeAction::Unchanged,
+                        Some(&current.fields),
+                        Some(&current.fields),
+                    ));
+                }
+            } else {
+                match self.update(
+                    Resource::UserStory,
+                    story.id,
+                    json!({ "status": target.id }),
+                ) {
+                    Ok(change) => entry.record(change),
+                    Err(err) => entry.fail(&err),
+                }
+            }
+
+            report.entries.push(entry);
+        }
+
+        Ok(report)
+    }
+
+    /// Deletes every selected story.
+    ///
+    /// Failures are reported per story rather than returned.
+    pub fn delete_stories(&self, selection: &Selection) -> BatchReport {
+        progress!("🗑️ Deleting {} user stories...", selection.stories.len());
+
+        let mut report = BatchReport::new(selection);
+        for story in &selection.stories {
+            let mut entry = BatchEntry::new(story.reference, Some(story.subject.clone()));
+            entry.id = Some(story.id);
+            entry.result = BatchResult::Deleted;
+
+            match self.delete_story(story.id) {
+                Ok(change) => entry.record(change),
+                Err(err) => entry.fail(&err),
+            }
+
+            report.entries.push(entry);
+        }
+
+        report
+    }
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.stories.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl BatchEntry {
+    fn new(reference: usize, subject: Option<String>) -> Self {
+        Self {
+            reference,
+            id: None,
+            subject,
+            result: BatchResult::Moved,
+            version: None,
+            old_status: None,
+            new_status: None,
+            url: None,
+            error: None,
+            exit_code: None,
+        }
+    }
+
+    fn record(&mut self, change: Change) {
+        self.version = change.version;
+        self.old_status = change.old_status;
+        self.new_status = change.new_status;
+        self.url = change.url;
+    }
+
+    fn fail(&mut self, err: &TaigaAPIError) {
+        self.result = BatchResult::Failed;
+        self.error = Some(err.to_string());
+        self.exit_code = Some(err.exit_code());
+    }
+
+    fn succeeded(&self) -> bool {
+        self.exit_code.is_none()
+    }
+}
+
+impl BatchReport {
+    /// A report starting with the refs of the selection that matched no story.
+    fn new(selection: &Selection) -> Self {
+        let entries = selection
+            .missing
+            .iter()
+            .map(|reference| {
+                let mut entry = BatchEntry::new(*reference, None);
+                entry.fail(&TaigaAPIError::StoryNotFound(format!(
+                    "User story with ref #{reference} not found."
+                )));
+                entry.result = BatchResult::NotFound;
+                entry
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// Exit code of the first story that failed, if any did.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.entries.iter().find_map(|entry| entry.exit_code)
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let reference = format!("#{}", entry.reference).bold().to_string();
+            let subject = entry.subject.as_deref().unwrap_or_default();
+
+            match entry.result {
+                BatchResult::Moved => writeln!(
+                    f,
+                    "✅ {reference} {subject}: {} → {}",
+                    entry.old_status.as_deref().unwrap_or_default(),
+                    entry
+                        .new_status
+                        .as_deref()
+                        .unwrap_or_default()
+                        .bright_green()
+                )?,
+                BatchResult::Unchanged => writeln!(
+                    f,
+                    "➖ {reference} {subject}: already in '{}'",
+                    entry.new_status.as_deref().unwrap_or_default()
+                )?,
+                BatchResult::Deleted => writeln!(f, "🗑️ {reference} {subject}: deleted")?,
+                BatchResult::NotFound => writeln!(f, "❌ {reference}: not found")?,
+                BatchResult::Failed => writeln!(
+                    f,
+                    "❌ {reference} {subject}: {}",
+                    entry.error.as_deref().unwrap_or_default()
+                )?,
+            }
+        }
+
+        let succeeded = self.entries.iter().filter(|e| e.succeeded()).count();
+        writeln!(
+            f,
+            "\n📊 {} succeeded, {} failed",
+            succeeded.bright_green(),
+            (self.entries.len() - succeeded).bright_red()
+        )
+    }
+}
@@ -0,0 +1,522 @@
+use core::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{ItemDetail, Resource, Status, TaigaAPI, TaigaAPIError};
+use crate::progress;
+
+/// A story to create, as read from an import file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewStory {
+    pub subject: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Name or slug of the status, or `new`/`wip`/`done`. Defaults to `new`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// A list, or a single comma-separated string as in CSV files.
+    #[serde(default, deserialize_with = "tag_list")]
+    pub tags: Vec<String>,
+    /// Username or full name of a project member.
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Ref of the epic to link the story to.
+    #[serde(default)]
+    pub epic: Option<usize>,
+    /// Name of the sprint to plan the story in.
+    #[serde(default)]
+    pub sprint: Option<String>,
+}
+
+/// Outcome of the import of each row.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct ImportReport {
+    rows: Vec<ImportRow>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportRow {
+    /// Position of the story in the file, starting at 1.
+    row: usize,
+    #[serde(rename = "ref")]
+    reference: Option<usize>,
+    subject: String,
+    result: RowResult,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum RowResult {
+    /// Checked by a dry run, nothing was created.
+    Valid,
+    Created,
+    Invalid,
+    Failed,
+}
+
+/// A row whose values were all found in the project.
+struct ResolvedStory {
+    row: usize,
+    status_id: usize,
+    milestone_id: Option<usize>,
+    epic_id: Option<usize>,
+    /// Fields `/userstories/bulk_create` cannot set, as a creation payload.
+    extra: Value,
+}
+
+impl ResolvedStory {
+    fn bulk_key(&self) -> (usize, Option<usize>) {
+        (self.status_id, self.milestone_id)
+    }
+}
+
+/// Body of `/userstories/bulk_create`; the stories are given as one subject per line.
+#[derive(Serialize)]
+struct BulkCreate<'a> {
+    project_id: usize,
+    status_id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone_id: Option<usize>,
+    bulk_stories: &'a str,
+}
+
+/// A story as `/userstories/bulk_create` returns it.
+#[derive(Deserialize)]
+struct BulkCreated {
+    subject: String,
+    #[serde(flatten)]
+    detail: ItemDetail,
+}
+
+impl TaigaAPI {
+    /// Creates many stories at once, after checking every one of them.
+    ///
+    /// Nothing is created when a row is invalid or on a `dry_run`. Stories that only set a
+    /// subject, status and sprint are created together through `/userstories/bulk_create`,
+    /// one request per status and sprint; the others are created one by one.
+    ///
+    /// Lookup and creation errors are reported per row rather than returned.
+    pub fn import_stories(
+        &self,
+        project_id: usize,
+        stories: &[NewStory],
+        dry_run: bool,
+    ) -> ImportReport {
+        let mut rows: Vec<ImportRow> = stories
+            .iter()
+            .enumerate()
+            .map(|(index, story)| ImportRow {
+                row: index + 1,
+                reference: None,
+                subject: story.subject.trim().to_owned(),
+                result: RowResult::Valid,
+                error: None,
+            })
+            .collect();
+
+        let mut lookups = Lookups::default();
+        let mut resolved = Vec::new();
+        for (row, story) in rows.iter_mut().zip(stories) {
+            match self.resolve_new_story(project_id, row.row, story, &mut lookups) {
+                Ok(story) => resolved.push(story),
+                Err(reason) => {
+                    row.result = RowResult::Invalid;
+                    row.error = Some(reason);
+                }
+            }
+        }
+
+        let any_invalid = rows.iter().any(|row| row.result == RowResult::Invalid);
+        if dry_run || any_invalid {
+            return ImportReport { rows };
+        }
+
+        let (bulk, single): (Vec<_>, Vec<_>) = resolved
+            .into_iter()
+            .partition(|story| story.extra == json!({}));
+
+        // `/userstories/bulk_create` takes a single status and sprint for all its stories.
+        let mut groups: Vec<Vec<ResolvedStory>> = Vec::new();
+        for story in bulk {
+            match groups
+                .iter_mut()
+                .find(|group| group[0].bulk_key() == story.bulk_key())
+            {
+                Some(group) => group.push(story),
+                None => groups.push(vec![story]),
+            }
+        }
+
+        for group in groups {
+            let (status_id, milestone_id) = group[0].bulk_key();
+            let subjects = group
+                .iter()
+                .map(|story| rows[story.row - 1].subject.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            match self.bulk_create_stories(project_id, status_id, milestone_id, &subjects) {
+                Ok(mut created) => {
+                    // Match by subject, so that a story Taiga skipped fails on its own row.
+                    for story in &group {
+                        let row = &mut rows[story.row - 1];
+                        match created.iter().position(|item| item.subject == row.subject) {
+                            Some(index) => {
+                                let detail = created.remove(index).detail;
+                                self.finish_import(project_id, story, &detail, row);
+                            }
+                            None => {
+                                row.result = RowResult::Failed;
+                                row.error = Some("Taiga did not create the story".to_owned());
+                            }
+                        }
+                    }
+                }
+                Err(err) => {
+                    for story in &group {
+                        let row = &mut rows[story.row - 1];
+                        row.result = RowResult::Failed;
+                        row.error = Some(err.to_string());
+                    }
+                }
+            }
+        }
+
+        for story in single {
+            let row = &mut rows[story.row - 1];
+
+            let mut payload = story.extra.clone();
+            payload["project"] = project_id.into();
+            payload["subject"] = row.subject.as_str().into();
+            payload["status"] = story.status_id.into();
+            if let Some(milestone_id) = story.milestone_id {
+                payload["milestone"] = milestone_id.into();
+            }
+
+            match self.create_item(Resource::UserStory, &payload) {
+                Ok(detail) => self.finish_import(project_id, &story, &detail, row),
+                Err(err) => {
+                    row.result = RowResult::Failed;
+                    row.error = Some(err.to_string());
+                }
+            }
+        }
+
+        self.ref_cache.borrow().persist();
+
+        ImportReport { rows }
+    }
+
+    /// Checks every value of a row against the project, returning why it is invalid otherwise.
+    fn resolve_new_story(
+        &self,
+        project_id: usize,
+        row: usize,
+        story: &NewStory,
+        lookups: &mut Lookups,
+    ) -> Result<ResolvedStory, String> {
+        let subject = story.subject.trim();
+        if subject.is_empty() {
+            return Err("The subject is empty".to_owned());
+        }
+        if subject.contains(['\n', '\r']) {
+            return Err("The subject must fit on one line".to_owned());
+        }
+
+        let status_id = match &story.status {
+            Some(name) => lookup(&mut lookups.statuses, name, || {
+                Ok(self
+                    .resolve_status(project_id, Resource::UserStory, name)?
+                    .id)
+            })?,
+            None => lookup(&mut lookups.statuses, "", || {
+                self.get_status_id(project_id, Resource::UserStory, &Status::New)
+            })?,
+        };
+
+        let milestone_id = story
+            .sprint
+            .as_deref()
+            .map(|name| {
+                lookup(&mut lookups.sprints, name, || {
+                    self.get_sprint_id(project_id, name)
+                })
+            })
+            .transpose()?;
+
+        let epic_id = story
+            .epic
+            .map(|epic_ref| {
+                lookup(&mut lookups.epics, &epic_ref.to_string(), || {
+                    self.get_epic_id(project_id, epic_ref)
+                })
+            })
+            .transpose()?;
+
+        let mut extra = json!({});
+        if let Some(description) = story.description.as_deref().filter(|d| !d.is_empty()) {
+            extra["description"] = description.into();
+        }
+        if !story.tags.is_empty() {
+            extra["tags"] = json!(story.tags);
+        }
+        if let Some(name) = &story.assignee {
+            extra["assigned_to"] = lookup(&mut lookups.members, name, || {
+                self.get_member_id(project_id, name)
+            })?
+            .into();
+        }
+
+        Ok(ResolvedStory {
+            row,
+            status_id,
+            milestone_id,
+            epic_id,
+            extra,
+        })
+    }
+
+    /// Creates one story per line of `subjects`.
+    fn bulk_create_stories(
+        &self,
+        project_id: usize,
+        status_id: usize,
+        milestone_id: Option<usize>,
+        subjects: &str,
+    ) -> Result<Vec<BulkCreated>, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        progress!(
+            "📦 Creating {} stories in one request...",
+            subjects.lines().count()
+        );
+
+        let response = self.send(
+            minreq::post(format!("{api_url}/userstories/bulk_create"))
+                .with_header("Content-Type", "application/json")
+                .with_json(&BulkCreate {
+                    project_id,
+                    status_id,
+                    milestone_id,
+                    bulk_stories: subjects,
+                })?,
+        )?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Creating the user stories failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        Ok(response.json()?)
+    }
+
+    /// Records a created story, remembering its ID and linking it to its epic.
+    fn finish_import(
+        &self,
+        project_id: usize,
+        story: &ResolvedStory,
+        detail: &ItemDetail,
+        row: &mut ImportRow,
+    ) {
+        self.ref_cache.borrow_mut().insert_story(
+            &self.api_url,
+            project_id,
+            detail.reference,
+            detail.id,
+        );
+
+        row.reference = Some(detail.reference);
+        row.result = RowResult::Created;
+
+        if let Some(epic_id) = story.epic_id {
+            if let Err(err) = self.link_epic(epic_id, detail.id) {
+                row.result = RowResult::Failed;
+                row.error = Some(format!("Created, but not linked to the epic: {err}"));
+            }
+        }
+    }
+}
+
+/// Values already looked up during an import, by the name used in the file.
+#[derive(Default)]
+struct Lookups {
+    statuses: HashMap<String, Result<usize, String>>,
+    members: HashMap<String, Result<usize, String>>,
+    sprints: HashMap<String, Result<usize, String>>,
+    epics: HashMap<String, Result<usize, String>>,
+}
+
+/// Looks `name` up once per import; later rows reuse the ID or the error.
+fn lookup(
+    memo: &mut HashMap<String, Result<usize, String>>,
+    name: &str,
+    resolve: impl FnOnce() -> Result<usize, TaigaAPIError>,
+) -> Result<usize, String> {
+    memo.entry(name.to_lowercase())
+        .or_insert_with(|| resolve().map_err(|err| err.to_string()))
+        .clone()
+}
+
+pub(super) fn tag_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    struct TagList;
+
+    impl<'de> de::Visitor<'de> for TagList {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a list of tags or a comma-separated string")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_owned)
+                .collect())
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+            Ok(vec![value.to_string()])
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            Ok(vec![value.to_string()])
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut tags = Vec::new();
+            while let Some(tag) = seq.next_element::<String>()? {
+                tags.push(tag);
+            }
+            Ok(tags)
+        }
+    }
+
+    deserializer.deserialize_any(TagList)
+}
+
+impl ImportReport {
+    /// Whether any row is invalid or could not be created.
+    pub fn has_errors(&self) -> bool {
+        self.rows
+            .iter()
+            .any(|row| matches!(row.result, RowResult::Invalid | RowResult::Failed))
+    }
+
+    pub fn count_invalid(&self) -> usize {
+        self.count(RowResult::Invalid)
+    }
+
+    fn count(&self, result: RowResult) -> usize {
+        self.rows.iter().filter(|row| row.result == result).count()
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "📥 Rows: {} — {} valid, {} created, {} invalid, {} failed\n",
+            self.rows.len(),
+            self.count(RowResult::Valid),
+            self.count(RowResult::Created),
+            self.count(RowResult::Invalid),
+            self.count(RowResult::Failed)
+        )?;
+
+        for row in &self.rows {
+            let reference = row
+                .reference
+                .map_or_else(|| "-".to_owned(), |reference| format!("#{reference}"));
+            // Padded before coloring, the escape codes would count towards the width.
+            let result = match row.result {
+                RowResult::Valid => format!("{:<8}", "valid").bright_green().to_string(),
+                RowResult::Created => format!("{:<8}", "created")
+                    .bright_green()
+                    .bold()
+                    .to_string(),
+                RowResult::Invalid => format!("{:<8}", "invalid").bright_red().to_string(),
+                RowResult::Failed => format!("{:<8}", "failed").bright_red().bold().to_string(),
+            };
+
+            writeln!(
+                f,
+                "  {:>4}  {:<6} {result} {}",
+                row.row, reference, row.subject
+            )?;
+            if let Some(error) = &row.error {
+                writeln!(f, "  {:>4}  {}", "", format!("↳ {error}").dimmed())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(json: Value) -> Vec<String> {
+        let story: NewStory =
+            serde_json::from_value(json!({ "subject": "Login", "tags": json })).unwrap();
+        story.tags
+    }
+
+    #[test]
+    fn tag_list_accepts_lists_and_comma_separated_strings() {
+        assert_eq!(tags(json!(["auth", "email"])), ["auth", "email"]);
+        assert_eq!(tags(json!("auth, email,,")), ["auth", "email"]);
+        assert_eq!(tags(json!(2025)), ["2025"]);
+        assert_eq!(tags(json!(null)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tag_list_reads_csv_cells() {
+        let csv = "subject,tags\nLogin,\"auth, email\"\nLogout,\n";
+        let stories = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize::<NewStory>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(stories[0].tags, ["auth", "email"]);
+        assert!(stories[1].tags.is_empty());
+    }
+
+    #[test]
+    fn tag_list_rejects_other_values() {
+        let story = serde_json::from_value::<NewStory>(json!({
+            "subject": "Login",
+            "tags": { "auth": true }
+        }));
+
+        assert!(story.is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{Resource, TaigaAPI, TAIGA_API_URL};
+
+/// Web interface of the hosted Taiga instance, matching [`TAIGA_API_URL`].
+const TAIGA_WEB_URL: &str = "https://tree.taiga.io";
+
+/// What a command did to a story, task, issue or epic, printed by `--format` so that
+/// scripts do not have to parse the progress lines.
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    /// `user_story`, `task`, `issue`, `epic` or `sprint`.
+    pub kind: &'static str,
+    pub action: ChangeAction,
+    /// `None` for sprints, which have no ref.
+    #[serde(rename = "ref")]
+    pub reference: Option<usize>,
+    pub id: usize,
+    /// Subject of the item, or name of the sprint.
+    pub subject: String,
+    /// Version of the item after the change, `None` once it is deleted.
+    pub version: Option<usize>,
+    /// Status before the change; `open` or `closed` for sprints.
+    pub old_status: Option<String>,
+    pub new_status: Option<String>,
+    /// Page of the item in the Taiga web interface.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    /// Nothing had to change, e.g. the item was already in the target status.
+    Unchanged,
+    Deleted,
+    /// The item was created by an earlier run, see `create --if-not-exists`.
+    Existing,
+    Linked,
+    Unlinked,
+}
+
+impl TaigaAPI {
+    /// Describes a change from the fields of an item as Taiga returned them `before` and
+    /// `after` it. Created items have no `before`, deleted items no `after`.
+    pub(super) fn change(
+        &self,
+        resource: Resource,
+        action: ChangeAction,
+        before: Option<&Value>,
+        after: Option<&Value>,
+    ) -> Change {
+        let fields = after.or(before).unwrap_or(&Value::Null);
+        let status = |fields: &Value| {
+            fields["status_extra_info"]["name"]
+                .as_str()
+                .map(str::to_owned)
+        };
+        let number = |value: &Value| value.as_u64().map(|n| n as usize);
+
+        Change {
+            kind: resource.kind(),
+            action,
+            reference: number(&fields["ref"]),
+            id: number(&fields["id"]).unwrap_or_default(),
+            subject: fields["subject"].as_str().unwrap_or_default().to_owned(),
+            version: after.and_then(|fields| number(&fields["version"])),
+            old_status: before.and_then(status),
+            new_status: after.and_then(status),
+            url: after.and_then(|fields| self.web_url(resource, fields)),
+        }
+    }
+
+    /// Link to an item in the web interface, e.g.
+    /// `https://tree.taiga.io/project/john-project/us/42`.
+    fn web_url(&self, resource: Resource, fields: &Value) -> Option<String> {
+        let slug = fields["project_extra_info"]["slug"].as_str()?;
+        let reference = fields["ref"].as_u64()?;
+
+        Some(format!(
+            "{}/project/{slug}/{}/{reference}",
+            self.web_root(),
+            resource.web_path()
+        ))
+    }
+
+    /// Describes a change to a sprint from its fields as Taiga returned them `before` and
+    /// `after` it. Its state, `open` or `closed`, stands for the status.
+    pub(super) fn sprint_change(
+        &self,
+        action: ChangeAction,
+        before: Option<&Value>,
+        after: &Value,
+    ) -> Change {
+        let state = |fields: &Value| {
+            let closed = fields["closed"].as_bool()?;
+            Some(if closed { "closed" } else { "open" }.to_owned())
+        };
+        let number = |value: &Value| value.as_u64().map(|n| n as usize);
+
+        // Sprints are shown on the taskboard, e.g.
+        // `https://tree.taiga.io/project/john-project/taskboard/sprint-3`.
+        let url = after["project_extra_info"]["slug"]
+            .as_str()
+            .zip(after["slug"].as_str())
+            .map(|(project, sprint)| {
+                format!("{}/project/{project}/taskboard/{sprint}", self.web_root())
+            });
+
+        Change {
+            kind: "sprint",
+            action,
+            reference: None,
+            id: number(&after["id"]).unwrap_or_default(),
+            subject: after["name"].as_str().unwrap_or_default().to_owned(),
+            version: number(&after["version"]),
+            old_status: before.and_then(state),
+            new_status: state(after),
+            url,
+        }
+    }
+
+    /// Root of the web interface of the instance.
+    fn web_root(&self) -> &str {
+        // Self-hosted instances serve the API under the web interface, at `/api/v1`.
+        if self.api_url == TAIGA_API_URL {
+            TAIGA_WEB_URL
+        } else {
+            self.api_url.trim_end_matches("/api/v1")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::integrations::taiga::AuthTokens;
+
+    fn api(api_url: &str) -> TaigaAPI {
+        let tokens = AuthTokens {
+            auth_token: "token".to_owned(),
+            refresh: "refresh".to_owned(),
+        };
+
+        TaigaAPI::from_tokens(api_url, tokens)
+    }
+
+    fn story(version: usize, status: &str) -> Value {
+        json!({
+            "id": 1234,
+            "ref": 42,
+            "subject": "Deploy v1.0.1",
+            "version": version,
+            "status_extra_info": { "name": status },
+            "project_extra_info": { "slug": "john-project" },
+        })
+    }
+
+    #[test]
+    fn change_describes_a_status_move() {
+        let before = story(3, "New");
+        let after = story(4, "In progress");
+
+        let change = api(TAIGA_API_URL).change(
+            Resource::UserStory,
+            ChangeAction::Updated,
+            Some(&before),
+            Some(&after),
+        );
+
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            json!({
+                "kind": "user_story",
+                "action": "updated",
+                "ref": 42,
+                "id": 1234,
+                "subject": "Deploy v1.0.1",
+                "version": 4,
+                "old_status": "New",
+                "new_status": "In progress",
+                "url": "https://tree.taiga.io/project/john-project/us/42",
+            })
+        );
+    }
+
+    #[test]
+    fn deleted_items_have_no_version_or_url() {
+        let before = story(3, "New");
+
+        let change =
+            api(TAIGA_API_URL).change(Resource::Task, ChangeAction::Deleted, Some(&before), None);
+
+        assert_eq!(change.kind, "task");
+        assert_eq!(change.reference, Some(42));
+        assert_eq!(change.version, None);
+        assert_eq!(change.new_status, None);
+        assert_eq!(change.url, None);
+    }
+
+    #[test]
+    fn urls_of_self_hosted_instances_drop_the_api_path() {
+        let after = story(1, "New");
+
+        let change = api("https://taiga.example.com/api/v1/").change(
+            Resource::Issue,
+            ChangeAction::Created,
+            None,
+            Some(&after),
+        );
+
+        assert_eq!(
+            change.url.as_deref(),
+            Some("https://taiga.example.com/project/john-project/issue/42")
+        );
+    }
+
+    #[test]
+    fn sprint_change_uses_its_state_as_status() {
+        let before = json!({ "id": 7, "name": "Sprint 3", "slug": "sprint-3", "closed": false });
+        let after = json!({
+            "id": 7,
+            "name": "Sprint 3",
+            "slug": "sprint-3",
+            "closed": true,
+            "project_extra_info": { "slug": "john-project" },
+        });
+
+        let change = api(TAIGA_API_URL).sprint_change(ChangeAction::Updated, Some(&before), &after);
+
+        assert_eq!(change.kind, "sprint");
+        assert_eq!(change.reference, None);
+        assert_eq!(change.subject, "Sprint 3");
+        assert_eq!(change.old_status.as_deref(), Some("open"));
+        assert_eq!(change.new_status.as_deref(), Some("closed"));
+        assert_eq!(
+            change.url.as_deref(),
+            Some("https://tree.taiga.io/project/john-project/taskboard/sprint-3")
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use super::{TaigaAPI, TaigaAPIError, UserStory};
+use crate::{
+    dates::Date,
+    git::{self, Commit},
+};
+
+/// How the stories of a changelog are grouped.
+#[derive(Debug, Clone, Copy, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// First tag of each story
+    Tag,
+    /// First Epic each story is linked to
+    Epic,
+}
+
+/// Text layout of a changelog.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ChangelogStyle {
+    /// A Markdown list per group
+    Markdown,
+    /// A release section as described in <https://keepachangelog.com>
+    KeepAChangelog,
+}
+
+/// Stories referenced by a set of commits, ready to be rendered as release notes.
+#[derive(Debug, Serialize)]
+pub struct Changelog {
+    /// Defaults to "Unreleased".
+    version: String,
+    date: Date,
+    group_by: GroupBy,
+    groups: Vec<ChangelogGroup>,
+    /// Refs mentioned in the commits that are not stories of the project.
+    unknown_refs: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChangelogGroup {
+    /// The tag or epic, `None` for the stories without one.
+    name: Option<String>,
+    stories: Vec<ChangelogEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChangelogEntry {
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+    status: String,
+    is_closed: bool,
+    section: Section,
+    /// SHAs of the commits mentioning the story.
+    commits: Vec<String>,
+}
+
+/// Keep-a-Changelog section of a story, after the conventional type of its commits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Section {
+    /// A `feat` commit mentions the story.
+    Added,
+    Changed,
+    /// A `fix` commit, and no `feat` one, mentions the story.
+    Fixed,
+}
+
+impl Section {
+    fn of(commits: &[&Commit]) -> Self {
+        let types = commits
+            .iter()
+            .filter_map(|commit| git::conventional_type(&commit.message))
+            .collect::<Vec<_>>();
+
+        if types.contains(&"feat") {
+            Self::Added
+        } else if types.contains(&"fix") {
+            Self::Fixed
+        } else {
+            Self::Changed
+        }
+    }
+
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Added => "Added",
+            Self::Changed => "Changed",
+            Self::Fixed => "Fixed",
+        }
+    }
+}
+
+impl TaigaAPI {
+    /// Builds the changelog of the stories mentioned by a set of commits.
+    ///
+    /// Only the stories mentioned are read. Only closed stories are included, unless
+    /// `include_open` is set.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the stories cannot be read.
+    pub fn changelog(
+        &self,
+        project_id: usize,
+        commits: &[Commit],
+        group_by: GroupBy,
+        include_open: bool,
+        version: Option<String>,
+    ) -> Result<Changelog, TaigaAPIError> {
+        let mut mentions: BTreeMap<usize, Vec<&Commit>> = BTreeMap::new();
+        for commit in commits {
+            for reference in git::mentioned_refs(&commit.message) {
+                mentions.entry(reference).or_default().push(commit);
+            }
+        }
+
+        let refs = mentions.keys().copied().collect::<Vec<_>>();
+        let stories = self
+            .stories_by_ref(project_id, &refs)?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<UserStory>, _>>()?;
+
+        let found: HashSet<usize> = stories.iter().map(|story| story.reference).collect();
+        let unknown_refs = refs
+            .iter()
+            .filter(|reference| !found.contains(reference))
+            .copied()
+            .collect();
+
+        let mut groups: BTreeMap<Option<String>, Vec<ChangelogEntry>> = BTreeMap::new();
+        for story in stories {
+            let Some(commits) = mentions.get(&story.reference) else {
+                continue;
+            };
+            if !include_open && !story.status_extra_info.is_closed {
+                continue;
+            }
+
+            groups
+                .entry(group_name(&story, group_by))
+                .or_default()
+                .push(ChangelogEntry {
+                    reference: story.reference,
+                    subject: story.subject,
+                    status: story.status_extra_info.name,
+                    is_closed: story.status_extra_info.is_closed,
+                    section: Section::of(commits),
+                    commits: commits
+                        .iter()
+                        .filter_map(|commit| commit.sha.clone())
+                        .collect(),
+                });
+        }
+
+        // Stories without a group go last.
+        let ungrouped = groups.remove(&None);
+        let mut groups = groups
+            .into_iter()
+            .chain(ungrouped.map(|stories| (None, stories)))
+            .map(|(name, stories)| ChangelogGroup { name, stories })
+            .collect::<Vec<_>>();
+
+        for group in &mut groups {
+            group.stories.sort_by_key(|entry| entry.reference);
+        }
+
+        Ok(Changelog {
+            version: version.unwrap_or_else(|| "Unreleased".to_owned()),
+            date: Date::today(),
+            group_by,
+            groups,
+            unknown_refs,
+        })
+    }
+}
+
+fn group_name(story: &UserStory, group_by: GroupBy) -> Option<String> {
+    match group_by {
+        GroupBy::Tag => story.tags.first().map(|(tag, _)| tag.clone()),
+        GroupBy::Epic => story
+            .epics
+            .iter()
+            .flatten()
+            .next()
+            .map(|epic| epic.subject.clone()),
+    }
+}
+
+impl Changelog {
+    pub fn unknown_refs(&self) -> &[usize] {
+        &self.unknown_refs
+    }
+
+    pub fn render(&self, style: ChangelogStyle) -> String {
+        match style {
+            ChangelogStyle::Markdown => self.render_markdown(),
+            ChangelogStyle::KeepAChangelog => self.render_keep_a_changelog(),
+        }
+    }
+
+    /// A section per group.
+    fn render_markdown(&self) -> String {
+        let mut text = format!("# {}\n", self.version);
+
+        for group in &self.groups {
+            text.push_str(&format!("\n## {}\n\n", self.group_heading(group)));
+
+            for entry in &group.stories {
+                text.push_str(&format!("- {}\n", entry.line()));
+            }
+        }
+
+        text
+    }
+
+    /// The Added, Changed and Fixed sections of a release, with the group of each story in
+    /// bold before it.
+    fn render_keep_a_changelog(&self) -> String {
+        let mut text = if self.version == "Unreleased" {
+            "## [Unreleased]\n".to_owned()
+        } else {
+            format!("## [{}] - {}\n", self.version, self.date)
+        };
+
+        let mut sections: BTreeMap<Section, Vec<String>> = BTreeMap::new();
+        for group in &self.groups {
+            for entry in &group.stories {
+                let line = match &group.name {
+                    Some(name) => format!("- **{name}**: {}", entry.line()),
+                    None => format!("- {}", entry.line()),
+                };
+                sections.entry(entry.section).or_default().push(line);
+            }
+        }
+
+        for (section, lines) in sections {
+            text.push_str(&format!("\n### {}\n\n", section.heading()));
+            for line in lines {
+                text.push_str(&line);
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    fn group_heading(&self, group: &ChangelogGroup) -> String {
+        match (&group.name, self.group_by) {
+            (Some(name), _) => name.clone(),
+            (None, GroupBy::Tag) => "Untagged".to_owned(),
+            (None, GroupBy::Epic) => "Without epic".to_owned(),
+        }
+    }
+}
+
+impl ChangelogEntry {
+    /// `Login with OAuth (#42)`, followed by the status of open stories.
+    fn line(&self) -> String {
+        let mut line = format!("{} (#{})", self.subject, self.reference);
+        if !self.is_closed {
+            line.push_str(&format!(" _{}_", self.status));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn commit(message: &str) -> Commit {
+        Commit {
+            sha: None,
+            message: message.to_owned(),
+        }
+    }
+
+    fn entry(reference: usize, subject: &str, section: Section) -> ChangelogEntry {
+        ChangelogEntry {
+            reference,
+            subject: subject.to_owned(),
+            status: "Done".to_owned(),
+            is_closed: true,
+            section,
+            commits: Vec::new(),
+        }
+    }
+
+    fn changelog(version: &str, groups: Vec<ChangelogGroup>) -> Changelog {
+        Changelog {
+            version: version.to_owned(),
+            date: "2025-07-01".parse().unwrap(),
+            group_by: GroupBy::Tag,
+            groups,
+            unknown_refs: Vec::new(),
+        }
+    }
+
+    fn user_story(tags: Value, epics: Value) -> UserStory {
+        serde_json::from_value(json!({
+            "id": 1,
+            "ref": 42,
+            "subject": "Login",
+            "status": 1,
+            "created_date": "2025-06-01T10:00:00Z",
+            "status_extra_info": { "name": "Done", "color": "#0f0", "is_closed": true },
+            "tags": tags,
+            "epics": epics,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn group_name_takes_the_first_tag_or_epic() {
+        let story = user_story(
+            json!([["backend", null], ["auth", null]]),
+            json!([{ "id": 3, "ref": 5, "subject": "Accounts", "color": "#f00" }]),
+        );
+        assert_eq!(group_name(&story, GroupBy::Tag).as_deref(), Some("backend"));
+        assert_eq!(
+            group_name(&story, GroupBy::Epic).as_deref(),
+            Some("Accounts")
+        );
+
+        let story = user_story(json!([]), json!(null));
+        assert_eq!(group_name(&story, GroupBy::Tag), None);
+        assert_eq!(group_name(&story, GroupBy::Epic), None);
+    }
+
+    #[test]
+    fn section_follows_the_conventional_type_of_the_commits() {
+        let feat = commit("feat(auth): OAuth login #42");
+        let fix = commit("fix: token expiry #42");
+        let docs = commit("docs: login #42");
+
+        assert_eq!(Section::of(&[&fix, &feat]), Section::Added);
+        assert_eq!(Section::of(&[&docs, &fix]), Section::Fixed);
+        assert_eq!(Section::of(&[&docs]), Section::Changed);
+        assert_eq!(Section::of(&[&commit("Login #42")]), Section::Changed);
+    }
+
+    #[test]
+    fn markdown_keeps_stories_without_a_group_apart_from_a_group_named_alike() {
+        let notes = changelog(
+            "1.2.0",
+            vec![
+                ChangelogGroup {
+                    name: Some("Untagged".to_owned()),
+                    stories: vec![entry(42, "Login", Section::Added)],
+                },
+                ChangelogGroup {
+                    name: None,
+                    stories: vec![entry(43, "Docs", Section::Changed)],
+                },
+            ],
+        );
+
+        assert_eq!(
+            notes.render(ChangelogStyle::Markdown),
+            "# 1.2.0\n\n## Untagged\n\n- Login (#42)\n\n## Untagged\n\n- Docs (#43)\n"
+        );
+        let json = serde_json::to_value(&notes).unwrap();
+        assert_eq!(json["groups"][1]["name"], Value::Null);
+    }
+
+    #[test]
+    fn keep_a_changelog_lists_stories_by_section() {
+        let mut open = entry(44, "Rate limit", Section::Fixed);
+        open.is_closed = false;
+        open.status = "In progress".to_owned();
+        let notes = changelog(
+            "1.2.0",
+            vec![
+                ChangelogGroup {
+                    name: Some("backend".to_owned()),
+                    stories: vec![entry(42, "Login", Section::Added), open],
+                },
+                ChangelogGroup {
+                    name: None,
+                    stories: vec![entry(43, "Docs", Section::Changed)],
+                },
+            ],
+        );
+
+        assert_eq!(
+            notes.render(ChangelogStyle::KeepAChangelog),
+            "## [1.2.0] - 2025-07-01\n\
+             \n### Added\n\n- **backend**: Login (#42)\n\
+             \n### Changed\n\n- Docs (#43)\n\
+             \n### Fixed\n\n- **backend**: Rate limit (#44) _In progress_\n"
+        );
+    }
+
+    #[test]
+    fn keep_a_changelog_leaves_the_date_out_of_unreleased_changes() {
+        assert_eq!(
+            changelog("Unreleased", Vec::new()).render(ChangelogStyle::KeepAChangelog),
+            "## [Unreleased]\n"
+        );
+    }
+}
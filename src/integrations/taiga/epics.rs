@@ -0,0 +1,257 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{
+    Change, ChangeAction, Resource, Status, StatusInfo, TaigaAPI, TaigaAPIError, UserStory,
+};
+use crate::{markdown, progress};
+
+impl TaigaAPI {
+    /// Lists all epics of the given project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn list_all_epics(&self, project_id: usize) -> Result<Vec<Epic>, TaigaAPIError> {
+        self.list_all(Resource::Epic, &format!("project={project_id}"))
+    }
+
+    /// Creates a new epic in the specified project and describes it as a change.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails, status cannot be found,
+    /// or the API response is invalid.
+    pub fn create_epic(
+        &self,
+        project_id: usize,
+        subject: &str,
+        description: &str,
+        status: &Status,
+    ) -> Result<Change, TaigaAPIError> {
+        let status_id = self.get_status_id(project_id, Resource::Epic, status)?;
+
+        let payload = json!({
+            "project": project_id,
+            "subject": subject,
+            "description": description,
+            "status": status_id
+        });
+
+        self.create(Resource::Epic, &payload)
+    }
+
+    /// Finds the internal epic ID from a reference number within a given project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::EpicNotFound` if the epic reference is not found.
+    pub fn get_epic_id(&self, project_id: usize, epic_ref: usize) -> Result<usize, TaigaAPIError> {
+        progress!("🔍 Looking up epic with ref #{epic_ref} in project...");
+
+        self.find_by_ref(Resource::Epic, project_id, epic_ref)?
+            .ok_or(TaigaAPIError::EpicNotFound(format!(
+                "Epic with ref #{epic_ref} not found."
+            )))
+    }
+
+    /// Fetches an epic together with its related user stories.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn get_epic_progress(
+        &self,
+        project_id: usize,
+        epic_id: usize,
+    ) -> Result<EpicProgress, TaigaAPIError> {
+        let epic: Epic = self.get(Resource::Epic, epic_id)?;
+        let mut stories: Vec<UserStory> = self.list_all(
+            Resource::UserStory,
+            &format!("project={project_id}&epic={epic_id}"),
+        )?;
+        self.categorize(&mut stories);
+
+        let closed = stories
+            .iter()
+            .filter(|story| story.status_extra_info.is_closed)
+            .count();
+
+        Ok(EpicProgress {
+            epic,
+            total: stories.len(),
+            closed,
+            stories,
+        })
+    }
+
+    /// Links a user story to an epic and describes the story as a change.
+    ///
+    /// Both are internal IDs, as returned by `get_epic_id` and `get_story_id`.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails, e.g. the story is already linked.
+    pub fn link_story_to_epic(
+        &self,
+        epic_id: usize,
+        user_story_id: usize,
+    ) -> Result<Change, TaigaAPIError> {
+        self.link_epic(epic_id, user_story_id)?;
+
+        self.story_change(ChangeAction::Linked, user_story_id)
+    }
+
+    /// Links a user story to an epic, without reading the story back.
+    pub(super) fn link_epic(
+        &self,
+        epic_id: usize,
+        user_story_id: usize,
+    ) -> Result<(), TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let payload = json!({
+            "epic": epic_id,
+            "user_story": user_story_id
+        });
+
+        let response = self.send(
+            minreq::post(format!("{api_url}/epics/{epic_id}/related_userstories"))
+                .with_header("Content-Type", "application/json")
+                .with_json(&payload)?,
+        )?;
+
+        if response.status_code != 201 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Linking the user story to the epic failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the link between a user story and an epic and describes the story as a change.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails, e.g. the story is not linked.
+    pub fn unlink_story_from_epic(
+        &self,
+        epic_id: usize,
+        user_story_id: usize,
+    ) -> Result<Change, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let response = self.send(minreq::delete(format!(
+            "{api_url}/epics/{epic_id}/related_userstories/{user_story_id}"
+        )))?;
+
+        if response.status_code != 204 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Unlinking the user story from the epic failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        self.story_change(ChangeAction::Unlinked, user_story_id)
+    }
+
+    /// A story as it is after a change that does not return it, such as linking it.
+    fn story_change(
+        &self,
+        action: ChangeAction,
+        user_story_id: usize,
+    ) -> Result<Change, TaigaAPIError> {
+        let fields: Value = self.get(Resource::UserStory, user_story_id)?;
+
+        Ok(self.change(Resource::UserStory, action, Some(&fields), Some(&fields)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-epic-detail-list>
+pub struct Epic {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+    #[serde(default)]
+    description: Option<String>,
+    status: usize,
+    created_date: String,
+    status_extra_info: StatusInfo,
+}
+
+impl fmt::Display for Epic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = if self.status_extra_info.is_closed {
+            self.reference.bright_green().bold().to_string()
+        } else {
+            self.reference.bright_magenta().bold().to_string()
+        };
+
+        write!(
+            f,
+            "#{:>2} {:<40} {}",
+            id,
+            self.subject,
+            self.status_extra_info.name.dimmed()
+        )
+    }
+}
+
+pub struct Epics(pub Vec<Epic>);
+
+impl fmt::Display for Epics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📋 Total epics: ({})\n", self.0.len())?;
+
+        for epic in &self.0 {
+            writeln!(f, "  {epic}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An epic with its related user stories and how many of them are closed.
+#[derive(Debug, Serialize)]
+pub struct EpicProgress {
+    pub epic: Epic,
+    pub total: usize,
+    pub closed: usize,
+    pub stories: Vec<UserStory>,
+}
+
+impl fmt::Display for EpicProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const WIDTH: usize = 20;
+
+        writeln!(f, "🏔️  Epic {}", self.epic)?;
+
+        if let Some(description) = self.epic.description.as_deref().filter(|d| !d.is_empty()) {
+            writeln!(f)?;
+            for line in markdown::render(description).lines() {
+                writeln!(f, "   {line}")?;
+            }
+        }
+
+        let percent = (self.closed * 100).checked_div(self.total).unwrap_or(0);
+        let filled = (self.closed * WIDTH).checked_div(self.total).unwrap_or(0);
+
+        writeln!(
+            f,
+            "\n   [{}{}] {}/{} stories closed ({percent}%)\n",
+            "█".repeat(filled).bright_green(),
+            "░".repeat(WIDTH - filled),
+            self.closed,
+            self.total
+        )?;
+
+        for story in &self.stories {
+            writeln!(f, "  {story}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use clap::Args;
+use std::str::FromStr;
+use urlencoding::encode;
+
+use super::{Resource, TaigaAPI, TaigaAPIError, UserStory};
+use crate::dates::Date;
+
+/// Criteria to narrow down a listing of user stories.
+///
+/// They are all sent to Taiga as query parameters, so only the matching stories are
+/// downloaded.
+#[derive(Args, Debug, Clone, Default)]
+pub struct StoryFilter {
+    /// Only stories in this status, by name or slug (repeatable)
+    #[arg(long = "status")]
+    pub status: Vec<String>,
+    /// Only stories with this tag (repeatable)
+    #[arg(long = "tag")]
+    pub tag: Vec<String>,
+    /// Only stories assigned to this member, by username or full name
+    #[arg(long = "assignee")]
+    pub assignee: Option<String>,
+    /// Only stories of this Sprint, by name
+    #[arg(long = "sprint", conflicts_with = "current_sprint")]
+    pub sprint: Option<String>,
+    /// Only stories of the Sprint running today
+    #[arg(long = "current-sprint")]
+    pub current_sprint: bool,
+    /// Only stories linked to the Epic with this ref
+    #[arg(long = "epic")]
+    pub epic: Option<usize>,
+    /// Only closed stories
+    #[arg(long = "closed", conflicts_with = "open")]
+    pub closed: bool,
+    /// Only open stories
+    #[arg(long = "open")]
+    pub open: bool,
+    /// Only stories created on or after this date (YYYY-MM-DD)
+    #[arg(long = "created-after")]
+    pub created_after: Option<Date>,
+    /// Only stories created before this date (YYYY-MM-DD)
+    #[arg(long = "created-before")]
+    pub created_before: Option<Date>,
+    /// Only stories modified on or after this date (YYYY-MM-DD)
+    #[arg(long = "modified-after")]
+    pub modified_after: Option<Date>,
+    /// Only stories modified before this date (YYYY-MM-DD)
+    #[arg(long = "modified-before")]
+    pub modified_before: Option<Date>,
+    /// Only stories matching this text, as in the search box of Taiga
+    #[arg(long = "search")]
+    pub search: Option<String>,
+}
+
+impl TaigaAPI {
+    /// Lists the user stories of the given project that match `filter`.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if a status, member, sprint or epic given in the
+    /// filter does not exist, or `TaigaAPIError::ApiError` if a request fails.
+    pub fn list_stories(
+        &self,
+        project_id: usize,
+        filter: &StoryFilter,
+    ) -> Result<Vec<UserStory>, TaigaAPIError> {
+        let query = self.story_query(project_id, filter)?;
+
+        let mut stories: Vec<UserStory> = self.list_all(Resource::UserStory, &query)?;
+        self.categorize(&mut stories);
+        self.set_status_order(project_id, &mut stories)?;
+
+        Ok(stories)
+    }
+
+    /// Translates `filter` into the query string of `/userstories`, resolving names to IDs.
+    ///
+    /// Values given by the user are percent-encoded, so that a tag such as `a&b` cannot
+    /// change the query.
+    pub(super) fn story_query(
+        &self,
+        project_id: usize,
+        filter: &StoryFilter,
+    ) -> Result<String, TaigaAPIError> {
+        let mut query = format!("project={project_id}");
+
+        if !filter.status.is_empty() {
+            let ids = filter
+                .status
+                .iter()
+                .map(|name| {
+                    self.resolve_status(project_id, Resource::UserStory, name)
+                        .map(|status| status.id.to_string())
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            query.push_str(&format!("&status={}", ids.join(",")));
+        }
+
+        if !filter.tag.is_empty() {
+            let tags = filter.tag.iter().map(|tag| encode(tag)).collect::<Vec<_>>();
+            query.push_str(&format!("&tags={}", tags.join(",")));
+        }
+
+        if let Some(assignee) = &filter.assignee {
+            query.push_str(&format!(
+                "&assigned_to={}",
+                self.get_member_id(project_id, assignee)?
+            ));
+        }
+
+        if let Some(sprint) = &filter.sprint {
+            query.push_str(&format!(
+                "&milestone={}",
+                self.get_sprint_id(project_id, sprint)?
+            ));
+        } else if filter.current_sprint {
+            query.push_str(&format!(
+                "&milestone={}",
+                self.get_current_sprint(project_id)?.id()
+            ));
+        }
+
+        if let Some(epic) = filter.epic {
+            query.push_str(&format!("&epic={}", self.get_epic_id(project_id, epic)?));
+        }
+
+        if filter.closed || filter.open {
+            query.push_str(&format!("&status__is_closed={}", filter.closed));
+        }
+
+        if let Some(search) = &filter.search {
+            query.push_str(&format!("&q={}", encode(search)));
+        }
+
+        let dates = [
+            ("created_date__gte", filter.created_after),
+            ("created_date__lt", filter.created_before),
+            ("modified_date__gte", filter.modified_after),
+            ("modified_date__lt", filter.modified_before),
+        ];
+
+        for (param, date) in dates {
+            if let Some(date) = date {
+                query.push_str(&format!("&{param}={}", encode(&date.to_string())));
+            }
+        }
+
+        Ok(query)
+    }
+}
+
+/// Parses a query such as `status=ready for test,tag=release,open`.
+///
+/// Keys are the flags of `list` without the dashes; `open`, `closed` and `current-sprint` take
+/// no value, and `status` and `tag` may be repeated.
+impl FromStr for StoryFilter {
+    type Err = String;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self::default();
+
+        for part in query
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            let (key, value) = match part.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim().to_owned())),
+                None => (part, None),
+            };
+
+            let value = || {
+                value
+                    .clone()
+                    .ok_or_else(|| format!("'{key}' needs a value"))
+            };
+            let date = || value()?.parse::<Date>();
+
+            match key {
+                "status" => filter.status.push(value()?),
+                "tag" => filter.tag.push(value()?),
+                "assignee" => filter.assignee = Some(value()?),
+                "sprint" => filter.sprint = Some(value()?),
+                "current-sprint" => filter.current_sprint = true,
+                "epic" => {
+                    filter.epic = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| format!("'{key}' needs the ref of an epic"))?,
+                    );
+                }
+                "closed" => filter.closed = true,
+                "open" => filter.open = true,
+                "created-after" => filter.created_after = Some(date()?),
+                "created-before" => filter.created_before = Some(date()?),
+                "modified-after" => filter.modified_after = Some(date()?),
+                "modified-before" => filter.modified_before = Some(date()?),
+                "search" => filter.search = Some(value()?),
+                _ => return Err(format!("Unknown filter '{key}'")),
+            }
+        }
+
+        if filter.open && filter.closed {
+            return Err("'open' and 'closed' cannot be combined".to_owned());
+        }
+
+        Ok(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrations::taiga::AuthTokens;
+
+    #[test]
+    fn from_str_reads_every_key() {
+        let filter: StoryFilter = "status=ready for test, status=new,tag=a&b,assignee=jdoe,\
+                                   sprint=Sprint 12,epic=60,open,created-after=2025-07-01,\
+                                   modified-before=2025-07-18,search=login"
+            .parse()
+            .unwrap();
+
+        assert_eq!(filter.status, ["ready for test", "new"]);
+        assert_eq!(filter.tag, ["a&b"]);
+        assert_eq!(filter.assignee.as_deref(), Some("jdoe"));
+        assert_eq!(filter.sprint.as_deref(), Some("Sprint 12"));
+        assert_eq!(filter.epic, Some(60));
+        assert!(filter.open && !filter.closed && !filter.current_sprint);
+        assert_eq!(filter.created_after, Date::from_ymd(2025, 7, 1));
+        assert_eq!(filter.modified_before, Date::from_ymd(2025, 7, 18));
+        assert_eq!(filter.search.as_deref(), Some("login"));
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_queries() {
+        let error = |query: &str| query.parse::<StoryFilter>().unwrap_err();
+
+        assert_eq!(error("owner=jdoe"), "Unknown filter 'owner'");
+        assert_eq!(error("status"), "'status' needs a value");
+        assert_eq!(error("epic=big"), "'epic' needs the ref of an epic");
+        assert_eq!(
+            error("open,closed"),
+            "'open' and 'closed' cannot be combined"
+        );
+        assert!(error("created-after=July").contains("not a valid date"));
+    }
+
+    #[test]
+    fn story_query_sends_the_search_text_to_taiga() {
+        let api = TaigaAPI::from_tokens(
+            "http://taiga.invalid/api/v1",
+            AuthTokens {
+                auth_token: "token".to_owned(),
+                refresh: "refresh".to_owned(),
+            },
+        );
+        let filter = StoryFilter {
+            search: Some("login & signup".to_owned()),
+            ..StoryFilter::default()
+        };
+
+        assert_eq!(
+            api.story_query(7, &filter).unwrap(),
+            "project=7&q=login%20%26%20signup"
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{Change, Resource, Status, StatusInfo, TaigaAPI, TaigaAPIError, UserInfo};
+use crate::{markdown, progress};
+
+/// A per-project issue classification that is referenced by its name.
+trait IssueAttribute {
+    /// Endpoint listing the values of the project.
+    const PATH: &'static str;
+    const LABEL: &'static str;
+
+    fn name(&self) -> &str;
+}
+
+macro_rules! issue_attribute {
+    ($(#[$meta:meta])* $name:ident, $path:literal, $label:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name(pub String);
+
+        impl IssueAttribute for $name {
+            const PATH: &'static str = $path;
+            const LABEL: &'static str = $label;
+
+            fn name(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(name: String) -> Self {
+                Self(name)
+            }
+        }
+    };
+}
+
+issue_attribute!(
+    /// Name of an issue type of the project, e.g. "Bug".
+    IssueType,
+    "issue-types",
+    "issue type"
+);
+issue_attribute!(
+    /// Name of a severity of the project, e.g. "Critical".
+    Severity,
+    "severities",
+    "severity"
+);
+issue_attribute!(
+    /// Name of a priority of the project, e.g. "High".
+    Priority,
+    "priorities",
+    "priority"
+);
+
+impl TaigaAPI {
+    /// Lists all issues of the given project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn list_all_issues(&self, project_id: usize) -> Result<Issues, TaigaAPIError> {
+        let mut issues: Vec<Issue> =
+            self.list_all(Resource::Issue, &format!("project={project_id}"))?;
+
+        let attributes = self.issue_attributes(project_id)?;
+        for issue in &mut issues {
+            issue.type_name = attributes.type_name(issue.issue_type);
+            issue.severity_name = attributes.severity_name(issue.severity);
+            issue.priority_name = attributes.priority_name(issue.priority);
+        }
+
+        Ok(Issues(issues))
+    }
+
+    fn issue_attributes(&self, project_id: usize) -> Result<IssueAttributes, TaigaAPIError> {
+        let names = |path| -> Result<HashMap<usize, String>, TaigaAPIError> {
+            Ok(self
+                .list_named(project_id, path)?
+                .into_iter()
+                .map(|v| (v.id, v.name))
+                .collect())
+        };
+
+        Ok(IssueAttributes {
+            types: names(IssueType::PATH)?,
+            severities: names(Severity::PATH)?,
+            priorities: names(Priority::PATH)?,
+        })
+    }
+
+    /// Creates a new issue and describes it as a change.
+    ///
+    /// Type, severity and priority are resolved by name; the project defaults are used for
+    /// the ones that are not given.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if a type, severity or priority does not exist,
+    /// or `TaigaAPIError::ApiError` if the request fails.
+    pub fn create_issue(
+        &self,
+        project_id: usize,
+        subject: &str,
+        description: &str,
+        issue_type: Option<&IssueType>,
+        severity: Option<&Severity>,
+        priority: Option<&Priority>,
+    ) -> Result<Change, TaigaAPIError> {
+        let mut payload = json!({
+            "project": project_id,
+            "subject": subject,
+            "description": description,
+        });
+
+        self.set_attribute(&mut payload, project_id, "type", issue_type)?;
+        self.set_attribute(&mut payload, project_id, "severity", severity)?;
+        self.set_attribute(&mut payload, project_id, "priority", priority)?;
+
+        self.create(Resource::Issue, &payload)
+    }
+
+    /// Finds the internal issue ID from a reference number within a given project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::IssueNotFound` if the issue reference is not found.
+    pub fn get_issue_id(
+        &self,
+        project_id: usize,
+        issue_ref: usize,
+    ) -> Result<usize, TaigaAPIError> {
+        progress!("🔍 Looking up issue with ref #{issue_ref} in project...");
+
+        self.find_by_ref(Resource::Issue, project_id, issue_ref)?
+            .ok_or(TaigaAPIError::IssueNotFound(format!(
+                "Issue with ref #{issue_ref} not found."
+            )))
+    }
+
+    /// Fetches the full detail of an issue.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn get_issue(
+        &self,
+        project_id: usize,
+        issue_id: usize,
+    ) -> Result<IssueDetail, TaigaAPIError> {
+        let mut issue: IssueDetail = self.get(Resource::Issue, issue_id)?;
+
+        let attributes = self.issue_attributes(project_id)?;
+        issue.type_name = attributes.type_name(issue.issue_type);
+        issue.severity_name = attributes.severity_name(issue.severity);
+        issue.priority_name = attributes.priority_name(issue.priority);
+
+        Ok(issue)
+    }
+
+    /// Updates the given fields of an issue, leaving the rest untouched.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if a type, severity, priority or status does not
+    /// exist, or `TaigaAPIError::ApiError` if the request fails.
+    pub fn update_issue(
+        &self,
+        project_id: usize,
+        issue_id: usize,
+        changes: &IssueChanges,
+    ) -> Result<Change, TaigaAPIError> {
+        let mut payload = Value::Object(Map::new());
+
+        if let Some(subject) = &changes.subject {
+            payload["subject"] = subject.as_str().into();
+        }
+        if let Some(description) = &changes.description {
+            payload["description"] = description.as_str().into();
+        }
+        if let Some(status) = &changes.status {
+            payload["status"] = self
+                .get_status_id(project_id, Resource::Issue, status)?
+                .into();
+        }
+
+        self.set_attribute(
+            &mut payload,
+            project_id,
+            "type",
+            changes.issue_type.as_ref(),
+        )?;
+        self.set_attribute(
+            &mut payload,
+            project_id,
+            "severity",
+            changes.severity.as_ref(),
+        )?;
+        self.set_attribute(
+            &mut payload,
+            project_id,
+            "priority",
+            changes.priority.as_ref(),
+        )?;
+
+        self.update(Resource::Issue, issue_id, payload)
+    }
+
+    /// Deletes an issue with the given internal ID from the Taiga project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the deletion fails.
+    pub fn delete_issue(&self, issue_id: usize) -> Result<Change, TaigaAPIError> {
+        self.delete(Resource::Issue, issue_id)
+    }
+
+    fn set_attribute<A: IssueAttribute>(
+        &self,
+        payload: &mut Value,
+        project_id: usize,
+        field: &str,
+        attribute: Option<&A>,
+    ) -> Result<(), TaigaAPIError> {
+        if let Some(attribute) = attribute {
+            payload[field] = self
+                .resolve_named(project_id, A::PATH, A::LABEL, attribute.name())?
+                .into();
+        }
+
+        Ok(())
+    }
+}
+
+/// Fields to change in an issue. `None` leaves a field as it is.
+#[derive(Debug, Default)]
+pub struct IssueChanges {
+    pub subject: Option<String>,
+    pub description: Option<String>,
+    pub issue_type: Option<IssueType>,
+    pub severity: Option<Severity>,
+    pub priority: Option<Priority>,
+    pub status: Option<Status>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-issue-detail-list>
+pub struct Issue {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+    status: usize,
+    #[serde(rename = "type")]
+    issue_type: Option<usize>,
+    severity: Option<usize>,
+    priority: Option<usize>,
+    created_date: String,
+    status_extra_info: StatusInfo,
+    /// Names of the type, severity and priority, resolved by `list_all_issues`.
+    #[serde(skip_deserializing)]
+    type_name: Option<String>,
+    #[serde(skip_deserializing)]
+    severity_name: Option<String>,
+    #[serde(skip_deserializing)]
+    priority_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-issue-detail-get>
+pub struct IssueDetail {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+    #[serde(default)]
+    description: Option<String>,
+    status: usize,
+    #[serde(rename = "type")]
+    issue_type: Option<usize>,
+    severity: Option<usize>,
+    priority: Option<usize>,
+    created_date: String,
+    modified_date: String,
+    #[serde(default)]
+    tags: Vec<(String, Option<String>)>,
+    version: usize,
+    status_extra_info: StatusInfo,
+    #[serde(default)]
+    assigned_to_extra_info: Option<UserInfo>,
+    /// Names of the type, severity and priority, resolved by `get_issue`.
+    #[serde(skip_deserializing)]
+    type_name: Option<String>,
+    #[serde(skip_deserializing)]
+    severity_name: Option<String>,
+    #[serde(skip_deserializing)]
+    priority_name: Option<String>,
+}
+
+impl fmt::Display for IssueDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "🐞 Issue #{} {}",
+            self.reference.bright_white().bold(),
+            self.subject.bold()
+        )?;
+        writeln!(f, "   Status:   {}", self.status_extra_info.name)?;
+
+        let assignee = self
+            .assigned_to_extra_info
+            .as_ref()
+            .map_or("Unassigned", |user| user.username.as_str());
+        writeln!(f, "   Assignee: {assignee}")?;
+
+        writeln!(
+            f,
+            "   Type:     {} · severity {} · priority {}",
+            or_dash(&self.type_name),
+            or_dash(&self.severity_name),
+            or_dash(&self.priority_name)
+        )?;
+        writeln!(f, "   Created:  {}", self.created_date)?;
+        writeln!(f, "   Modified: {}", self.modified_date)?;
+
+        if !self.tags.is_empty() {
+            let tags = self
+                .tags
+                .iter()
+                .map(|(tag, _)| tag.as_str())
+                .collect::<Vec<_>>();
+            writeln!(f, "   Tags:     {}", tags.join(", "))?;
+        }
+
+        if let Some(description) = self.description.as_deref().filter(|d| !d.is_empty()) {
+            writeln!(f)?;
+            for line in markdown::render(description).lines() {
+                writeln!(f, "   {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Names of the issue types, severities and priorities of a project, by ID.
+struct IssueAttributes {
+    types: HashMap<usize, String>,
+    severities: HashMap<usize, String>,
+    priorities: HashMap<usize, String>,
+}
+
+impl IssueAttributes {
+    fn type_name(&self, id: Option<usize>) -> Option<String> {
+        name(&self.types, id)
+    }
+
+    fn severity_name(&self, id: Option<usize>) -> Option<String> {
+        name(&self.severities, id)
+    }
+
+    fn priority_name(&self, id: Option<usize>) -> Option<String> {
+        name(&self.priorities, id)
+    }
+}
+
+fn name(names: &HashMap<usize, String>, id: Option<usize>) -> Option<String> {
+    id.and_then(|id| names.get(&id)).cloned()
+}
+
+fn or_dash(name: &Option<String>) -> &str {
+    name.as_deref().unwrap_or("-")
+}
+
+/// Issues of a project, with the names of their type, severity and priority.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Issues(pub Vec<Issue>);
+
+impl fmt::Display for Issues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📋 Total issues: ({})\n", self.0.len())?;
+
+        for issue in &self.0 {
+            let id = if issue.status_extra_info.is_closed {
+                issue.reference.bright_green().bold().to_string()
+            } else {
+                issue.reference.bright_red().bold().to_string()
+            };
+
+            writeln!(
+                f,
+                "  #{:>2} {:<40} {:<12} {:<10} {:<10} {}",
+                id,
+                issue.subject,
+                or_dash(&issue.type_name),
+                or_dash(&issue.severity_name),
+                or_dash(&issue.priority_name),
+                issue.status_extra_info.name.dimmed()
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{Status, UserStory};
+use crate::dates::Date;
+
+/// Order of a story listing.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    Ref,
+    /// Oldest first
+    Created,
+    /// Least recently modified first
+    Modified,
+    /// Order of the statuses on the project board
+    Status,
+    /// Position in the backlog, as ranked in Taiga
+    Priority,
+    /// Username of the assignee, unassigned stories last
+    Assignee,
+}
+
+impl SortKey {
+    /// Sorts the stories, keeping the current order of the ones that compare equal.
+    pub fn sort(self, stories: &mut [UserStory]) {
+        match self {
+            SortKey::Ref => stories.sort_by_key(|story| story.reference),
+            SortKey::Created => stories.sort_by(|a, b| a.created_date.cmp(&b.created_date)),
+            SortKey::Modified => stories.sort_by(|a, b| a.modified_date.cmp(&b.modified_date)),
+            SortKey::Status => stories.sort_by_key(|story| story.status_order),
+            SortKey::Priority => stories.sort_by_key(|story| story.backlog_order),
+            SortKey::Assignee => stories.sort_by_cached_key(|story| {
+                let assignee = story.assignee().map(str::to_lowercase);
+                (assignee.is_none(), assignee)
+            }),
+        }
+    }
+}
+
+/// Extra field shown after the subject of each story in a listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Assignee,
+    Tags,
+    Points,
+    Sprint,
+    /// Days since the story was created
+    Age,
+}
+
+impl UserStory {
+    fn assignee(&self) -> Option<&str> {
+        self.assigned_to_extra_info
+            .as_ref()
+            .map(|user| user.username.as_str())
+    }
+
+    fn age(&self) -> Option<i64> {
+        Date::parse_prefix(&self.created_date).map(|created| Date::today().days_since(created))
+    }
+
+    /// The story as a line of a listing, followed by the given columns.
+    pub(super) fn row(&self, columns: &[Column]) -> String {
+        let mut row = self.to_string();
+
+        for column in columns {
+            let value = match column {
+                Column::Assignee => format!("{:<14}", self.assignee().unwrap_or("-")),
+                Column::Tags => {
+                    let tags = self
+                        .tags
+                        .iter()
+                        .map(|(tag, _)| tag.as_str())
+                        .collect::<Vec<_>>();
+                    format!("{:<20}", tags.join(","))
+                }
+                Column::Points => {
+                    let points = self.total_points.map_or("-".to_owned(), |p| p.to_string());
+                    format!("{points:>5}")
+                }
+                Column::Sprint => {
+                    format!("{:<16}", self.milestone_name.as_deref().unwrap_or("-"))
+                }
+                Column::Age => {
+                    let age = self.age().map_or("-".to_owned(), |days| format!("{days}d"));
+                    format!("{age:>5}")
+                }
+            };
+
+            row.push(' ');
+            row.push_str(&value.dimmed().to_string());
+        }
+
+        row
+    }
+}
+
+/// Fields of a story available to `--template`.
+#[derive(Debug, Serialize)]
+pub struct StoryView<'a> {
+    #[serde(rename = "ref")]
+    reference: usize,
+    id: usize,
+    subject: &'a str,
+    /// Name of the status, e.g. "In progress".
+    status: &'a str,
+    status_id: usize,
+    is_closed: bool,
+    /// `new`, `wip` or `done`, according to the status mapping.
+    category: Option<&'static str>,
+    assignee: Option<&'a str>,
+    tags: Vec<&'a str>,
+    points: Option<f64>,
+    sprint: Option<&'a str>,
+    created: &'a str,
+    modified: &'a str,
+    /// Days since the story was created.
+    age: Option<i64>,
+}
+
+/// Stories of one status, for templates that loop over `groups`.
+#[derive(Debug, Serialize)]
+pub struct GroupView<'a> {
+    status: &'a str,
+    category: Option<&'static str>,
+    stories: Vec<StoryView<'a>>,
+}
+
+/// Everything a template that renders a whole listing can use.
+#[derive(Debug, Serialize)]
+pub struct ListingView<'a> {
+    total: usize,
+    /// Stories in the order of the listing.
+    stories: Vec<StoryView<'a>>,
+    /// Stories grouped by status, in the order of the project board.
+    groups: Vec<GroupView<'a>>,
+}
+
+impl UserStory {
+    pub fn view(&self) -> StoryView<'_> {
+        StoryView {
+            reference: self.reference,
+            id: self.id,
+            subject: &self.subject,
+            status: &self.status_extra_info.name,
+            status_id: self.status,
+            is_closed: self.status_extra_info.is_closed,
+            category: self.category.as_ref().map(category_name),
+            assignee: self.assignee(),
+            tags: self.tags.iter().map(|(tag, _)| tag.as_str()).collect(),
+            points: self.total_points,
+            sprint: self.milestone_name.as_deref(),
+            created: &self.created_date,
+            modified: &self.modified_date,
+            age: self.age(),
+        }
+    }
+}
+
+impl<'a> ListingView<'a> {
+    pub fn new(stories: &'a [UserStory]) -> Self {
+        let mut groups: BTreeMap<(i64, usize), GroupView<'a>> = BTreeMap::new();
+
+        for story in stories {
+            groups
+                .entry((story.status_order, story.status))
+                .or_insert_with(|| GroupView {
+                    status: &story.status_extra_info.name,
+                    category: story.category.as_ref().map(category_name),
+                    stories: Vec::new(),
+                })
+                .stories
+                .push(story.view());
+        }
+
+        Self {
+            total: stories.len(),
+            stories: stories.iter().map(UserStory::view).collect(),
+            groups: groups.into_values().collect(),
+        }
+    }
+}
+
+fn category_name(status: &Status) -> &'static str {
+    match status {
+        Status::New => "new",
+        Status::Wip => "wip",
+        Status::Done => "done",
+    }
+}
@@ -0,0 +1,678 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{bulk::tag_list, Resource, Status, StatusDetail, TaigaAPI, TaigaAPIError};
+use crate::progress;
+
+/// Prefix of the tag that ties a story of Taiga to its entry in the backlog file.
+const EXTERNAL_TAG: &str = "ext:";
+
+/// A story as it should be in Taiga, as read from a backlog file.
+///
+/// Fields left out are not managed by the file: they are set freely in Taiga and never
+/// reverted.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BacklogStory {
+    /// Stable key of the story, stored in Taiga as the tag `ext:<id>`.
+    pub id: String,
+    pub subject: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Name or slug of the status, or `new`/`wip`/`done`. New stories default to `new`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// The tags of the story besides its `ext:` tag; an empty list is not managed.
+    #[serde(default, deserialize_with = "tag_list")]
+    pub tags: Vec<String>,
+    /// Username or full name of a project member.
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Name of the sprint the story is planned in.
+    #[serde(default)]
+    pub sprint: Option<String>,
+    /// Ref of an epic the story is linked to.
+    #[serde(default)]
+    pub epic: Option<usize>,
+}
+
+/// Changes that bring the stories of Taiga in line with a backlog file.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Plan {
+    steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlanStep {
+    action: PlanAction,
+    external_id: String,
+    #[serde(rename = "ref")]
+    reference: Option<usize>,
+    subject: String,
+    changes: Vec<FieldChange>,
+    /// Set once the plan is applied.
+    result: Option<StepResult>,
+    error: Option<String>,
+    #[serde(skip)]
+    id: Option<usize>,
+    /// Request body of the creation or update.
+    #[serde(skip)]
+    payload: Value,
+    #[serde(skip)]
+    epic_id: Option<usize>,
+    /// Description the story has once the step is applied, if the file manages it.
+    #[serde(skip)]
+    description: Option<String>,
+    #[serde(skip)]
+    exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PlanAction {
+    Create,
+    Update,
+    /// The story is no longer in the file.
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum StepResult {
+    Applied,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+struct FieldChange {
+    field: &'static str,
+    from: String,
+    to: String,
+}
+
+/// IDs of the names used in the file, resolved once per plan.
+#[derive(Default)]
+struct Resolved {
+    statuses: HashMap<String, StatusDetail>,
+    members: HashMap<String, usize>,
+    sprints: HashMap<String, usize>,
+    epics: HashMap<usize, usize>,
+}
+
+impl TaigaAPI {
+    /// Compares the stories of a backlog file with those of the project.
+    ///
+    /// Stories are matched through their `ext:<id>` tag. Stories missing from Taiga are
+    /// created, managed fields that differ are updated, and open stories with an `ext:` tag
+    /// that are no longer in the file are closed. Nothing is changed until the plan is given
+    /// to [`TaigaAPI::apply_plan`].
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if a status, member, sprint or epic of the file
+    /// does not exist, or `TaigaAPIError::ApiError` if a request fails.
+    pub fn plan_backlog(
+        &self,
+        project_id: usize,
+        backlog: &[BacklogStory],
+    ) -> Result<Plan, TaigaAPIError> {
+        progress!("🔍 Comparing {} stories with the project...", backlog.len());
+
+        let items: Vec<Value> =
+            self.list_all(Resource::UserStory, &format!("project={project_id}"))?;
+
+        // Stories with a lower ref win when several carry the same `ext:` tag.
+        let mut managed: Vec<(String, &Value)> = items
+            .iter()
+            .filter_map(|item| Some((external_id(item)?, item)))
+            .collect();
+        managed.sort_by_key(|(_, item)| item["ref"].as_u64());
+
+        let mut resolved = Resolved::default();
+        let mut steps = Vec::new();
+
+        for story in backlog {
+            let key = story.id.to_lowercase();
+            let existing = managed
+                .iter()
+                .find(|(id, _)| *id == key)
+                .map(|(_, item)| *item);
+
+            let step = match existing {
+                Some(item) => self.plan_update(project_id, story, item, &mut resolved),
+                None => self.plan_create(project_id, story, &mut resolved).map(Some),
+            }
+            .map_err(|err| in_story(err, &story.id))?;
+
+            if let Some(step) = step {
+                steps.push(step);
+            }
+        }
+        self.ref_cache.borrow().persist();
+
+        let to_close = managed
+            .iter()
+            .filter(|(id, item)| {
+                !is_closed(item)
+                    && !backlog
+                        .iter()
+                        .any(|story| story.id.eq_ignore_ascii_case(id))
+            })
+            .collect::<Vec<_>>();
+
+        if !to_close.is_empty() {
+            let done = self.category_status(project_id, Resource::UserStory, &Status::Done)?;
+
+            for (id, item) in to_close {
+                let mut step = PlanStep::new(PlanAction::Close, id, item);
+                step.change(
+                    "status",
+                    text(&item["status_extra_info"]["name"]),
+                    &done.name,
+                );
+                step.payload["status"] = done.id.into();
+                steps.push(step);
+            }
+        }
+
+        Ok(Plan { steps })
+    }
+
+    /// Carries out the steps of a plan, in order.
+    ///
+    /// A failed step does not stop the others; each one records its own result.
+    pub fn apply_plan(&self, project_id: usize, mut plan: Plan) -> Plan {
+        for step in &mut plan.steps {
+            let outcome = match step.action {
+                PlanAction::Create => {
+                    self.create_item(Resource::UserStory, &step.payload)
+                        .map(|detail| {
+                            step.id = Some(detail.id);
+                            step.reference = Some(detail.reference);
+                            self.ref_cache.borrow_mut().insert_story(
+                                &self.api_url,
+                                project_id,
+                                detail.reference,
+                                detail.id,
+                            );
+                            Some(detail.version)
+                        })
+                }
+                PlanAction::Update | PlanAction::Close if step.payload == json!({}) => Ok(None),
+                PlanAction::Update | PlanAction::Close => self
+                    .update(Resource::UserStory, step.id(), step.payload.clone())
+                    .map(|change| change.version),
+            };
+
+            // The next plan can trust the description it was just given.
+            let outcome = outcome.map(|version| {
+                if let (Some(version), Some(description)) = (version, &step.description) {
+                    self.ref_cache.borrow_mut().insert_description(
+                        &self.api_url,
+                        step.id(),
+                        version,
+                        description,
+                    );
+                }
+            });
+
+            let outcome = outcome.and_then(|()| match (step.epic_id, step.id) {
+                (Some(epic_id), Some(id)) => self.link_epic(epic_id, id),
+                _ => Ok(()),
+            });
+
+            match outcome {
+                Ok(()) => step.result = Some(StepResult::Applied),
+                Err(err) => {
+                    step.result = Some(StepResult::Failed);
+                    step.error = Some(err.to_string());
+                    step.exit_code = Some(err.exit_code());
+                }
+            }
+        }
+
+        self.ref_cache.borrow().persist();
+
+        plan
+    }
+
+    fn plan_create(
+        &self,
+        project_id: usize,
+        story: &BacklogStory,
+        resolved: &mut Resolved,
+    ) -> Result<PlanStep, TaigaAPIError> {
+        let status = self.resolve_backlog_status(project_id, story.status.as_deref(), resolved)?;
+
+        let mut payload = json!({
+            "project": project_id,
+            "subject": story.subject,
+            "status": status.id,
+            "tags": desired_tags(story),
+        });
+        if let Some(description) = &story.description {
+            payload["description"] = description.as_str().into();
+        }
+        if let Some(name) = &story.assignee {
+            payload["assigned_to"] = self.resolve_member(project_id, name, resolved)?.into();
+        }
+        if let Some(name) = &story.sprint {
+            payload["milestone"] = self.resolve_sprint(project_id, name, resolved)?.into();
+        }
+        let epic_id = story
+            .epic
+            .map(|epic| self.resolve_epic(project_id, epic, resolved))
+            .transpose()?;
+
+        Ok(PlanStep {
+            action: PlanAction::Create,
+            external_id: story.id.clone(),
+            reference: None,
+            subject: story.subject.clone(),
+            changes: Vec::new(),
+            result: None,
+            error: None,
+            id: None,
+            payload,
+            epic_id,
+            description: story.description.clone(),
+            exit_code: None,
+        })
+    }
+
+    /// The update that brings `item` in line with `story`, or `None` if they already match.
+    fn plan_update(
+        &self,
+        project_id: usize,
+        story: &BacklogStory,
+        item: &Value,
+        resolved: &mut Resolved,
+    ) -> Result<Option<PlanStep>, TaigaAPIError> {
+        let mut step = PlanStep::new(PlanAction::Update, &story.id, item);
+
+        if text(&item["subject"]) != story.subject {
+            step.change("subject", text(&item["subject"]), &story.subject);
+            step.payload["subject"] = story.subject.as_str().into();
+        }
+
+        // The base of the update: the listed story, or its detail once that was read.
+        let mut base = item.clone();
+
+        if let Some(description) = &story.description {
+            step.description = Some(description.clone());
+
+            let version = item["version"].as_u64().unwrap_or_default() as usize;
+            let known = self.ref_cache.borrow().has_description(
+                &self.api_url,
+                step.id(),
+                version,
+                description,
+            );
+
+            // Listings leave the description out, only the detail of the story has it.
+            if !known && item.get("description").is_none() {
+                base = self.get(Resource::UserStory, step.id())?;
+            }
+
+            let current = text(&base["description"]);
+            if known || current.trim() == description.trim() {
+                self.ref_cache.borrow_mut().insert_description(
+                    &self.api_url,
+                    step.id(),
+                    version,
+                    description,
+                );
+            } else {
+                step.change("description", short(&current), &short(description));
+                step.payload["description"] = description.as_str().into();
+            }
+        }
+
+        if let Some(name) = &story.status {
+            let status = self.resolve_backlog_status(project_id, Some(name), resolved)?;
+            if item["status"] != status.id {
+                step.change(
+                    "status",
+                    text(&item["status_extra_info"]["name"]),
+                    &status.name,
+                );
+                step.payload["status"] = status.id.into();
+            }
+        }
+
+        if !story.tags.is_empty() {
+            let current = current_tags(item);
+            let desired = desired_tags(story);
+            if current.iter().collect::<BTreeSet<_>>() != desired.iter().collect() {
+                step.change("tags", current.join(", "), &desired.join(", "));
+                step.payload["tags"] = json!(desired);
+            }
+        }
+
+        if let Some(name) = &story.assignee {
+            let member_id = self.resolve_member(project_id, name, resolved)?;
+            if item["assigned_to"] != member_id {
+                step.change(
+                    "assignee",
+                    text(&item["assigned_to_extra_info"]["username"]),
+                    name,
+                );
+                step.payload["assigned_to"] = member_id.into();
+            }
+        }
+
+        if let Some(name) = &story.sprint {
+            let sprint_id = self.resolve_sprint(project_id, name, resolved)?;
+            if item["milestone"] != sprint_id {
+                step.change("sprint", text(&item["milestone_name"]), name);
+                step.payload["milestone"] = sprint_id.into();
+            }
+        }
+
+        if let Some(epic) = story.epic {
+            let linked = item["epics"]
+                .as_array()
+                .is_some_and(|epics| epics.iter().any(|linked| linked["ref"] == epic));
+            if !linked {
+                step.change("epic", String::new(), &format!("#{epic}"));
+                step.epic_id = Some(self.resolve_epic(project_id, epic, resolved)?);
+            }
+        }
+
+        if step.changes.is_empty() {
+            return Ok(None);
+        }
+
+        if step.payload != json!({}) {
+            self.remember(Resource::UserStory, base)?;
+        }
+
+        Ok(Some(step))
+    }
+
+    fn resolve_backlog_status(
+        &self,
+        project_id: usize,
+        name: Option<&str>,
+        resolved: &mut Resolved,
+    ) -> Result<StatusDetail, TaigaAPIError> {
+        let key = name.unwrap_or_default().to_lowercase();
+        if let Some(status) = resolved.statuses.get(&key) {
+            return Ok(status.clone());
+        }
+
+        let status = match name {
+            Some(name) => self.resolve_status(project_id, Resource::UserStory, name)?,
+            None => self.category_status(project_id, Resource::UserStory, &Status::New)?,
+        };
+        resolved.statuses.insert(key, status.clone());
+
+        Ok(status)
+    }
+
+    fn resolve_member(
+        &self,
+        project_id: usize,
+        name: &str,
+        resolved: &mut Resolved,
+    ) -> Result<usize, TaigaAPIError> {
+        if let Some(id) = resolved.members.get(&name.to_lowercase()) {
+            return Ok(*id);
+        }
+
+        let id = self.get_member_id(project_id, name)?;
+        resolved.members.insert(name.to_lowercase(), id);
+
+        Ok(id)
+    }
+
+    fn resolve_sprint(
+        &self,
+        project_id: usize,
+        name: &str,
+        resolved: &mut Resolved,
+    ) -> Result<usize, TaigaAPIError> {
+        if let Some(id) = resolved.sprints.get(&name.to_lowercase()) {
+            return Ok(*id);
+        }
+
+        let id = self.get_sprint_id(project_id, name)?;
+        resolved.sprints.insert(name.to_lowercase(), id);
+
+        Ok(id)
+    }
+
+    fn resolve_epic(
+        &self,
+        project_id: usize,
+        epic_ref: usize,
+        resolved: &mut Resolved,
+    ) -> Result<usize, TaigaAPIError> {
+        if let Some(id) = resolved.epics.get(&epic_ref) {
+            return Ok(*id);
+        }
+
+        let id = self.get_epic_id(project_id, epic_ref)?;
+        resolved.epics.insert(epic_ref, id);
+
+        Ok(id)
+    }
+}
+
+impl PlanStep {
+    /// A step on an existing story, with no change yet.
+    fn new(action: PlanAction, external_id: &str, item: &Value) -> Self {
+        Self {
+            action,
+            external_id: external_id.to_owned(),
+            reference: item["ref"].as_u64().map(|reference| reference as usize),
+            subject: text(&item["subject"]),
+            changes: Vec::new(),
+            result: None,
+            error: None,
+            id: item["id"].as_u64().map(|id| id as usize),
+            payload: json!({}),
+            epic_id: None,
+            description: None,
+            exit_code: None,
+        }
+    }
+
+    fn id(&self) -> usize {
+        self.id.unwrap_or_default()
+    }
+
+    fn change(&mut self, field: &'static str, from: String, to: &str) {
+        self.changes.push(FieldChange {
+            field,
+            from,
+            to: to.to_owned(),
+        });
+    }
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Exit code of the first step that failed, if any did.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.steps.iter().find_map(|step| step.exit_code)
+    }
+
+    fn count(&self, action: PlanAction) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| step.action == action)
+            .count()
+    }
+}
+
+/// `auth-login` from a story tagged `ext:auth-login`.
+fn external_id(item: &Value) -> Option<String> {
+    current_tags(item)
+        .into_iter()
+        .find_map(|tag| Some(tag.strip_prefix(EXTERNAL_TAG)?.to_owned()))
+}
+
+/// Names of the tags of a story; Taiga sends each one as a `[name, color]` pair.
+fn current_tags(item: &Value) -> Vec<String> {
+    item["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag[0].as_str().or(tag.as_str()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The tags of the file plus the `ext:` tag, lowercased as Taiga stores them.
+fn desired_tags(story: &BacklogStory) -> Vec<String> {
+    let mut tags = story
+        .tags
+        .iter()
+        .map(|tag| tag.to_lowercase())
+        .collect::<Vec<_>>();
+    tags.push(format!("{EXTERNAL_TAG}{}", story.id.to_lowercase()));
+    tags
+}
+
+fn is_closed(item: &Value) -> bool {
+    item["is_closed"].as_bool() == Some(true)
+        || item["status_extra_info"]["is_closed"].as_bool() == Some(true)
+}
+
+fn text(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_owned()
+}
+
+/// The first line of a long text, to show it in a plan.
+fn short(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() > 40 || text.trim().lines().count() > 1 {
+        format!("{}…", line.chars().take(40).collect::<String>())
+    } else {
+        line.to_owned()
+    }
+}
+
+/// Names the story of the file that a lookup failed for.
+fn in_story(err: TaigaAPIError, id: &str) -> TaigaAPIError {
+    match err {
+        TaigaAPIError::ValueNotFound(msg) => {
+            TaigaAPIError::ValueNotFound(format!("{msg} (story '{id}')"))
+        }
+        TaigaAPIError::EpicNotFound(msg) => {
+            TaigaAPIError::EpicNotFound(format!("{msg} (story '{id}')"))
+        }
+        err => err,
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.steps.is_empty() {
+            return writeln!(f, "✅ Taiga matches the backlog file, nothing to do");
+        }
+
+        writeln!(
+            f,
+            "📋 Plan: {} to create, {} to update, {} to close\n",
+            self.count(PlanAction::Create).bright_green(),
+            self.count(PlanAction::Update).bright_yellow(),
+            self.count(PlanAction::Close).bright_red()
+        )?;
+
+        for step in &self.steps {
+            let marker = match step.result {
+                Some(StepResult::Applied) => "✅ ",
+                Some(StepResult::Failed) => "❌ ",
+                None => "",
+            };
+            let action = match step.action {
+                PlanAction::Create => "+".bright_green().bold().to_string(),
+                PlanAction::Update => "~".bright_yellow().bold().to_string(),
+                PlanAction::Close => "-".bright_red().bold().to_string(),
+            };
+            let reference = step
+                .reference
+                .map_or_else(String::new, |reference| format!("#{reference}"));
+
+            writeln!(
+                f,
+                "  {marker}{action} {:<20} {} {}",
+                step.external_id,
+                format!("{reference:<6}").bold(),
+                step.subject
+            )?;
+
+            for change in &step.changes {
+                writeln!(
+                    f,
+                    "        {}: {} → {}",
+                    change.field.dimmed(),
+                    change.from,
+                    change.to
+                )?;
+            }
+            if let Some(error) = &step.error {
+                writeln!(f, "        {}", format!("↳ {error}").bright_red())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backlog_story(id: &str, tags: &[&str]) -> BacklogStory {
+        BacklogStory {
+            id: id.to_owned(),
+            subject: "Login".to_owned(),
+            description: None,
+            status: None,
+            tags: tags.iter().map(|tag| (*tag).to_owned()).collect(),
+            assignee: None,
+            sprint: None,
+            epic: None,
+        }
+    }
+
+    #[test]
+    fn tags_are_read_from_pairs_or_names() {
+        let item = json!({ "tags": [["Auth", "#f00"], ["ext:auth-login", null], "ui"] });
+
+        assert_eq!(current_tags(&item), ["auth", "ext:auth-login", "ui"]);
+        assert_eq!(current_tags(&json!({})), Vec::<String>::new());
+    }
+
+    #[test]
+    fn desired_tags_end_with_the_external_tag() {
+        let story = backlog_story("Auth-Login", &["Auth", "ui"]);
+
+        assert_eq!(desired_tags(&story), ["auth", "ui", "ext:auth-login"]);
+    }
+
+    #[test]
+    fn external_id_comes_from_the_ext_tag() {
+        let item = json!({ "tags": [["auth", null], ["ext:auth-login", null]] });
+
+        assert_eq!(external_id(&item).as_deref(), Some("auth-login"));
+        assert_eq!(external_id(&json!({ "tags": [["auth", null]] })), None);
+    }
+
+    #[test]
+    fn long_texts_are_shortened_to_their_first_line() {
+        assert_eq!(short("  Revoke every session. "), "Revoke every session.");
+        assert_eq!(short("First line\nSecond line"), "First line…");
+        assert_eq!(short(&"a".repeat(50)), format!("{}…", "a".repeat(40)));
+    }
+}
@@ -0,0 +1,300 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{Change, ChangeAction, Resource, TaigaAPI, TaigaAPIError, UserStory};
+use crate::dates::Date;
+
+impl TaigaAPI {
+    /// Lists all sprints (milestones) of the given project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn list_sprints(&self, project_id: usize) -> Result<Vec<Sprint>, TaigaAPIError> {
+        let mut sprints: Vec<Sprint> =
+            self.list_all_from("milestones", &format!("project={project_id}"))?;
+        sprints.sort_by_key(|sprint| sprint.estimated_start);
+
+        Ok(sprints)
+    }
+
+    /// Creates a new sprint in the given project.
+    ///
+    /// Returns the change, with the sprint as Taiga created it.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails, e.g. a sprint with the same
+    /// name already exists.
+    pub fn create_sprint(
+        &self,
+        project_id: usize,
+        name: &str,
+        start: Date,
+        finish: Date,
+    ) -> Result<Change, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let payload = json!({
+            "project": project_id,
+            "name": name,
+            "estimated_start": start,
+            "estimated_finish": finish
+        });
+
+        let response = self.send(
+            minreq::post(format!("{api_url}/milestones"))
+                .with_header("Content-Type", "application/json")
+                .with_json(&payload)?,
+        )?;
+
+        if response.status_code != 201 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Creating new sprint failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        let fields: Value = response.json()?;
+
+        Ok(self.sprint_change(ChangeAction::Created, None, &fields))
+    }
+
+    /// Finds the ID of a sprint by its name, compared case-insensitively.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound`, listing the sprints of the project, if none matches.
+    pub fn get_sprint_id(&self, project_id: usize, name: &str) -> Result<usize, TaigaAPIError> {
+        let sprints = self.list_sprints(project_id)?;
+
+        sprints
+            .iter()
+            .find(|sprint| sprint.name.eq_ignore_ascii_case(name))
+            .map(|sprint| sprint.id)
+            .ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(format!(
+                    "No sprint named '{name}'. Available: {}",
+                    sprints
+                        .iter()
+                        .map(|sprint| sprint.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })
+    }
+
+    /// Finds the open sprint whose estimated dates include today.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if no open sprint is running today.
+    pub fn get_current_sprint(&self, project_id: usize) -> Result<Sprint, TaigaAPIError> {
+        let today = Date::today();
+
+        self.list_sprints(project_id)?
+            .into_iter()
+            .find(|sprint| sprint.is_current(today))
+            .ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(format!("No open sprint is running today ({today})."))
+            })
+    }
+
+    /// Fetches a sprint together with its user stories.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn get_sprint(&self, sprint_id: usize) -> Result<SprintDetail, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let response = self.send(minreq::get(format!("{api_url}/milestones/{sprint_id}")))?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Fetching the sprint failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        let mut sprint: SprintDetail = response.json()?;
+        self.categorize(&mut sprint.user_stories);
+
+        Ok(sprint)
+    }
+
+    /// Marks a sprint as closed, unless it already is.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails.
+    pub fn close_sprint(&self, sprint_id: usize) -> Result<Change, TaigaAPIError> {
+        let api_url = self.api_url.clone();
+
+        let before: Value = self.fetch(&format!("milestones/{sprint_id}"))?;
+        if before["closed"].as_bool() == Some(true) {
+            return Ok(self.sprint_change(ChangeAction::Unchanged, Some(&before), &before));
+        }
+
+        let response = self.send(
+            minreq::patch(format!("{api_url}/milestones/{sprint_id}"))
+                .with_header("Content-Type", "application/json")
+                .with_json(&json!({ "closed": true }))?,
+        )?;
+
+        if response.status_code != 200 {
+            let body = response.as_str()?;
+            return Err(TaigaAPIError::ApiError(format!(
+                "Closing the sprint failed. HTTP {}: {}",
+                response.status_code, body
+            )));
+        }
+
+        let after: Value = response.json()?;
+
+        Ok(self.sprint_change(ChangeAction::Updated, Some(&before), &after))
+    }
+
+    /// Assigns a user story to a sprint.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the story cannot be retrieved or updated.
+    pub fn move_story_to_sprint(
+        &self,
+        user_story_id: usize,
+        sprint_id: usize,
+    ) -> Result<Change, TaigaAPIError> {
+        self.update(
+            Resource::UserStory,
+            user_story_id,
+            json!({ "milestone": sprint_id }),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-milestone-detail>
+pub struct Sprint {
+    id: usize,
+    name: String,
+    estimated_start: Date,
+    estimated_finish: Date,
+    closed: bool,
+    total_points: Option<f64>,
+    closed_points: Option<f64>,
+}
+
+impl Sprint {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    fn is_current(&self, today: Date) -> bool {
+        !self.closed && self.estimated_start <= today && today <= self.estimated_finish
+    }
+}
+
+impl fmt::Display for Sprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.closed {
+            "closed".dimmed().to_string()
+        } else if self.is_current(Date::today()) {
+            "current".bright_green().bold().to_string()
+        } else {
+            "open".bright_yellow().to_string()
+        };
+
+        write!(
+            f,
+            "🏃 {:<24} {} → {}  {}",
+            self.name.bold(),
+            self.estimated_start,
+            self.estimated_finish,
+            state
+        )
+    }
+}
+
+pub struct Sprints(pub Vec<Sprint>);
+
+impl fmt::Display for Sprints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📋 Total sprints: ({})\n", self.0.len())?;
+
+        for sprint in &self.0 {
+            writeln!(f, "  {sprint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-milestone-detail>
+pub struct SprintDetail {
+    #[serde(flatten)]
+    sprint: Sprint,
+    user_stories: Vec<UserStory>,
+}
+
+impl fmt::Display for SprintDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.sprint)?;
+
+        if let (Some(total), Some(closed)) = (self.sprint.total_points, self.sprint.closed_points) {
+            writeln!(f, "   Points: {closed}/{total} closed")?;
+        }
+
+        let closed = self
+            .user_stories
+            .iter()
+            .filter(|story| story.status_extra_info.is_closed)
+            .count();
+        writeln!(
+            f,
+            "   Stories: {closed}/{} closed\n",
+            self.user_stories.len()
+        )?;
+
+        for story in &self.user_stories {
+            writeln!(f, "  {story}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprint(start: Date, finish: Date, closed: bool) -> Sprint {
+        Sprint {
+            id: 1,
+            name: "Sprint 1".to_owned(),
+            estimated_start: start,
+            estimated_finish: finish,
+            closed,
+            total_points: None,
+            closed_points: None,
+        }
+    }
+
+    fn date(day: u32) -> Date {
+        Date::from_ymd(2025, 5, day).unwrap()
+    }
+
+    #[test]
+    fn sprints_are_current_on_their_first_and_last_day() {
+        let sprint = sprint(date(5), date(16), false);
+
+        assert!(!sprint.is_current(date(4)));
+        assert!(sprint.is_current(date(5)));
+        assert!(sprint.is_current(date(16)));
+        assert!(!sprint.is_current(date(17)));
+    }
+
+    #[test]
+    fn closed_sprints_are_never_current() {
+        assert!(!sprint(date(5), date(16), true).is_current(date(10)));
+    }
+}
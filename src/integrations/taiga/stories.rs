@@ -0,0 +1,736 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use urlencoding::encode;
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{
+    Change, ChangeAction, ItemVersion, Resource, StatusInfo, TaigaAPI, TaigaAPIError, Task,
+    UserInfo,
+};
+use crate::{dates::Date, markdown};
+
+/// How many of the latest comments `get_story` includes.
+const RECENT_COMMENTS: usize = 5;
+
+impl TaigaAPI {
+    /// Fetches the full detail of a user story, together with its tasks, custom attributes,
+    /// attachments and latest comments.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if a request fails or the API response is invalid.
+    pub fn get_story(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+    ) -> Result<StoryDetail, TaigaAPIError> {
+        // The detail read when looking the story up by ref, if it was.
+        let current = self.retrieve_current_version(Resource::UserStory, user_story_id)?;
+        let mut story: StoryDetail = serde_json::from_value(current.fields)?;
+
+        let members = self.list_members(project_id)?;
+        story.watcher_names = story
+            .watchers
+            .iter()
+            .map(|id| {
+                members
+                    .iter()
+                    .find(|member| member.id == *id)
+                    .map_or_else(|| format!("#{id}"), |member| member.username.clone())
+            })
+            .collect();
+
+        story.custom_attributes = self.story_custom_attributes(project_id, user_story_id)?;
+        story.tasks = self.list_all_tasks(project_id, Some(user_story_id))?;
+        story.attachments = self.fetch(&format!(
+            "userstories/attachments?project={project_id}&object_id={user_story_id}"
+        ))?;
+        story.comments = self.story_comments(user_story_id)?;
+
+        Ok(story)
+    }
+
+    /// Applies `changes` to a user story on top of its current version.
+    ///
+    /// Returns the change, with the version of the story after the update.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if the assignee or the points do not exist in the
+    /// project, or `TaigaAPIError::ApiError` if a request fails.
+    pub fn edit_story(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+        changes: &StoryChanges,
+    ) -> Result<Change, TaigaAPIError> {
+        let current = self.retrieve_current_version(Resource::UserStory, user_story_id)?;
+
+        self.edit_story_from(project_id, user_story_id, changes, current)
+    }
+
+    /// Moves a user story to a status, given by name, slug or `new`/`wip`/`done`, leaving
+    /// `comment` on it. Stories already in that status are left untouched.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if the status does not exist, or
+    /// `TaigaAPIError::ApiError` if a request fails.
+    pub fn transition_story(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+        status: &str,
+        comment: &str,
+    ) -> Result<Change, TaigaAPIError> {
+        let target = self.resolve_status(project_id, Resource::UserStory, status)?;
+        let current = self.retrieve_current_version(Resource::UserStory, user_story_id)?;
+
+        if current.fields["status"] == target.id {
+            return Ok(self.change(
+                Resource::UserStory,
+                ChangeAction::Unchanged,
+                Some(&current.fields),
+                Some(&current.fields),
+            ));
+        }
+
+        self.update_from(
+            Resource::UserStory,
+            user_story_id,
+            json!({ "status": target.id, "comment": comment }),
+            current,
+        )
+    }
+
+    /// Finds the story a `create` may have made already, so that it is not made twice.
+    ///
+    /// With an idempotency `key`, that is a story tagged as [`idempotency_tag`] gives it;
+    /// otherwise a story with exactly this subject, among those Taiga finds when searching for
+    /// it. Returns the oldest match.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the stories cannot be listed.
+    pub fn find_created_story(
+        &self,
+        project_id: usize,
+        subject: &str,
+        key: Option<&str>,
+    ) -> Result<Option<Change>, TaigaAPIError> {
+        let stories: Vec<Value> = match key {
+            Some(key) => {
+                let tag = idempotency_tag(key);
+                let tagged: Vec<Value> = self.list_all(
+                    Resource::UserStory,
+                    &format!("project={project_id}&tags={}", encode(&tag)),
+                )?;
+                // Tags come as `[name, color]` pairs.
+                tagged
+                    .into_iter()
+                    .filter(|story| {
+                        story["tags"]
+                            .as_array()
+                            .is_some_and(|tags| tags.iter().any(|pair| pair[0] == *tag))
+                    })
+                    .collect()
+            }
+            None => {
+                // Taiga's search narrows the listing down, the subject must still match whole.
+                let found: Vec<Value> = self.list_all(
+                    Resource::UserStory,
+                    &format!("project={project_id}&q={}", encode(subject.trim())),
+                )?;
+                found
+                    .into_iter()
+                    .filter(|story| {
+                        story["subject"]
+                            .as_str()
+                            .is_some_and(|other| other.trim() == subject.trim())
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(stories
+            .iter()
+            .min_by_key(|story| story["ref"].as_u64())
+            .map(|story| {
+                self.change(
+                    Resource::UserStory,
+                    ChangeAction::Existing,
+                    Some(story),
+                    Some(story),
+                )
+            }))
+    }
+
+    /// The subject and current status of a user story.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the story cannot be fetched.
+    pub fn get_story_status(&self, user_story_id: usize) -> Result<StoryStatus, TaigaAPIError> {
+        let current = self.retrieve_current_version(Resource::UserStory, user_story_id)?;
+
+        Ok(serde_json::from_value(current.fields)?)
+    }
+
+    /// The editable fields of a user story, as shown to the user by `edit` when no flag is given.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if a request fails.
+    pub fn story_fields(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+    ) -> Result<StoryFields, TaigaAPIError> {
+        let current = self.retrieve_current_version(Resource::UserStory, user_story_id)?;
+        let story: EditableStory = serde_json::from_value(current.fields.clone())?;
+
+        let points = match self.estimating_role(project_id)? {
+            Some(role) => match story.points.get(&role.to_string()).copied().flatten() {
+                Some(point_id) => self
+                    .list_named(project_id, "points")?
+                    .into_iter()
+                    .find(|point| point.id == point_id)
+                    .map(|point| point.name)
+                    .unwrap_or_default(),
+                None => String::new(),
+            },
+            None => String::new(),
+        };
+
+        Ok(StoryFields {
+            subject: story.subject,
+            assignee: story
+                .assigned_to_extra_info
+                .map(|user| user.username)
+                .unwrap_or_default(),
+            tags: story.tags.into_iter().map(|(tag, _)| tag).collect(),
+            points,
+            due_date: story.due_date.unwrap_or_default(),
+            blocked: story.is_blocked,
+            blocked_note: story.blocked_note,
+            description: story.description.unwrap_or_default(),
+            base: current,
+        })
+    }
+
+    /// Saves the fields edited from `original`, as returned by [`TaigaAPI::story_fields`].
+    ///
+    /// Only the fields that differ are sent, on top of the version the fields were read at. If
+    /// someone else changed the story in the meantime, the update is only retried when they
+    /// changed other fields.
+    ///
+    /// Returns the change, whose action is `unchanged` if no field was edited.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if a value is not valid for the project, or
+    /// `TaigaAPIError::ApiError` if a request fails.
+    pub fn save_story_fields(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+        original: &StoryFields,
+        edited: &StoryFields,
+    ) -> Result<Change, TaigaAPIError> {
+        let changes = original.changes(edited)?;
+        if changes.is_empty() {
+            let fields = &original.base.fields;
+            return Ok(self.change(
+                Resource::UserStory,
+                ChangeAction::Unchanged,
+                Some(fields),
+                Some(fields),
+            ));
+        }
+
+        self.edit_story_from(project_id, user_story_id, &changes, original.base.clone())
+    }
+
+    fn edit_story_from(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+        changes: &StoryChanges,
+        base: ItemVersion,
+    ) -> Result<Change, TaigaAPIError> {
+        let mut payload = json!({});
+
+        if let Some(subject) = &changes.subject {
+            payload["subject"] = subject.as_str().into();
+        }
+        if let Some(description) = &changes.description {
+            payload["description"] = description.as_str().into();
+        }
+        if let Some(tags) = &changes.tags {
+            payload["tags"] = json!(tags);
+        }
+        if let Some(assignee) = &changes.assignee {
+            payload["assigned_to"] = match assignee {
+                Some(name) => self.get_member_id(project_id, name)?.into(),
+                None => Value::Null,
+            };
+        }
+        if let Some(points) = &changes.points {
+            let role = self.estimating_role(project_id)?.ok_or_else(|| {
+                TaigaAPIError::ValueNotFound(
+                    "The project has no role that estimates points".to_owned(),
+                )
+            })?;
+            let point = self.resolve_named(project_id, "points", "point value", points)?;
+
+            payload["points"] = json!({ role.to_string(): point });
+        }
+        if let Some(due_date) = &changes.due_date {
+            payload["due_date"] = due_date.map_or(Value::Null, |date| date.to_string().into());
+        }
+        if let Some(blocked) = changes.blocked {
+            payload["is_blocked"] = blocked.into();
+        }
+        if let Some(note) = &changes.blocked_note {
+            payload["blocked_note"] = note.as_str().into();
+        }
+
+        self.update_from(Resource::UserStory, user_story_id, payload, base)
+    }
+
+    /// The first role of the project whose estimations count towards the story points.
+    fn estimating_role(&self, project_id: usize) -> Result<Option<usize>, TaigaAPIError> {
+        let mut roles: Vec<Role> = self.fetch(&format!("roles?project={project_id}"))?;
+        roles.sort_by_key(|role| role.order);
+
+        Ok(roles
+            .into_iter()
+            .find(|role| role.computable)
+            .map(|role| role.id))
+    }
+
+    /// Names and values of the custom attributes of a story that have a value.
+    fn story_custom_attributes(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+    ) -> Result<Vec<CustomAttribute>, TaigaAPIError> {
+        let attributes = self.list_named(project_id, "userstory-custom-attributes")?;
+        if attributes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let values: CustomAttributesValues = self.fetch(&format!(
+            "userstories/custom-attributes-values/{user_story_id}"
+        ))?;
+
+        Ok(attributes
+            .into_iter()
+            .filter_map(|attribute| {
+                let value = values.attributes_values.get(&attribute.id.to_string())?;
+                let value = match value {
+                    Value::Null => return None,
+                    Value::String(s) if s.is_empty() => return None,
+                    Value::String(s) => s.clone(),
+                    value => value.to_string(),
+                };
+
+                Some(CustomAttribute {
+                    name: attribute.name,
+                    value,
+                })
+            })
+            .collect())
+    }
+
+    /// The latest comments of a story, oldest first.
+    fn story_comments(&self, user_story_id: usize) -> Result<Vec<Comment>, TaigaAPIError> {
+        let mut comments: Vec<Comment> =
+            self.fetch(&format!("history/userstory/{user_story_id}?type=comment"))?;
+
+        comments.retain(|c| !c.comment.trim().is_empty() && c.delete_comment_date.is_none());
+        comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        let skip = comments.len().saturating_sub(RECENT_COMMENTS);
+        Ok(comments.split_off(skip))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-userstory-detail-get>
+pub struct StoryDetail {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+    #[serde(default)]
+    description: Option<String>,
+    version: usize,
+    created_date: String,
+    modified_date: String,
+    status_extra_info: StatusInfo,
+    #[serde(default)]
+    assigned_to_extra_info: Option<UserInfo>,
+    #[serde(default)]
+    tags: Vec<(String, Option<String>)>,
+    #[serde(default)]
+    total_points: Option<f64>,
+    #[serde(default)]
+    milestone_name: Option<String>,
+    /// Taiga sends `null` rather than an empty list when the story has no epic.
+    #[serde(default)]
+    epics: Option<Vec<StoryEpic>>,
+    #[serde(default)]
+    watchers: Vec<usize>,
+    /// Usernames of the watchers, resolved by `get_story`.
+    #[serde(skip_deserializing)]
+    watcher_names: Vec<String>,
+    #[serde(skip_deserializing)]
+    custom_attributes: Vec<CustomAttribute>,
+    #[serde(skip_deserializing)]
+    tasks: Vec<Task>,
+    #[serde(skip_deserializing)]
+    attachments: Vec<Attachment>,
+    /// The latest comments, oldest first.
+    #[serde(skip_deserializing)]
+    comments: Vec<Comment>,
+}
+
+/// Returned by [`TaigaAPI::get_story_status`].
+#[derive(Debug, Deserialize)]
+pub struct StoryStatus {
+    pub subject: String,
+    #[serde(rename = "status_extra_info")]
+    pub status: StatusInfo,
+}
+
+/// Tag that stores the idempotency key of a story made by `create`, lowercased as Taiga
+/// stores tags.
+pub fn idempotency_tag(key: &str) -> String {
+    format!("key:{}", key.to_lowercase())
+}
+
+/// Fields to change in a user story. `None` leaves a field as it is.
+#[derive(Debug, Default)]
+pub struct StoryChanges {
+    pub subject: Option<String>,
+    pub description: Option<String>,
+    /// Replaces all the tags of the story.
+    pub tags: Option<Vec<String>>,
+    /// Username or full name of the new assignee; `Some(None)` unassigns the story.
+    pub assignee: Option<Option<String>>,
+    /// Name of a point value of the project, e.g. "3" or "?", for the estimating role.
+    pub points: Option<String>,
+    /// `Some(None)` removes the due date.
+    pub due_date: Option<Option<Date>>,
+    pub blocked: Option<bool>,
+    pub blocked_note: Option<String>,
+}
+
+impl StoryChanges {
+    pub fn is_empty(&self) -> bool {
+        self.subject.is_none()
+            && self.description.is_none()
+            && self.tags.is_none()
+            && self.assignee.is_none()
+            && self.points.is_none()
+            && self.due_date.is_none()
+            && self.blocked.is_none()
+            && self.blocked_note.is_none()
+    }
+}
+
+/// The editable fields of a user story, written as the front matter of the document `edit`
+/// opens in `$EDITOR`. Empty strings stand for unset values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoryFields {
+    pub subject: String,
+    /// Username of the assignee.
+    #[serde(default)]
+    pub assignee: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Point value of the estimating role, e.g. "3" or "?".
+    #[serde(default)]
+    pub points: String,
+    /// YYYY-MM-DD
+    #[serde(default)]
+    pub due_date: String,
+    #[serde(default)]
+    pub blocked: bool,
+    #[serde(default)]
+    pub blocked_note: String,
+    /// Markdown body of the document.
+    #[serde(skip)]
+    pub description: String,
+    /// The story the fields were read from.
+    #[serde(skip)]
+    base: ItemVersion,
+}
+
+impl StoryFields {
+    /// The changes that turn `self` into `edited`.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ValueNotFound` if the edited due date is not a valid date.
+    fn changes(&self, edited: &StoryFields) -> Result<StoryChanges, TaigaAPIError> {
+        fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+            (old != new).then(|| new.clone())
+        }
+
+        let non_empty = |value: &str| (!value.trim().is_empty()).then(|| value.trim().to_owned());
+
+        let due_date = match changed(&self.due_date, &edited.due_date) {
+            Some(date) => Some(
+                non_empty(&date)
+                    .map(|date| date.parse::<Date>())
+                    .transpose()
+                    .map_err(TaigaAPIError::ValueNotFound)?,
+            ),
+            None => None,
+        };
+
+        Ok(StoryChanges {
+            subject: changed(&self.subject, &edited.subject),
+            description: (self.description.trim() != edited.description.trim())
+                .then(|| edited.description.clone()),
+            tags: changed(&self.tags, &edited.tags),
+            assignee: changed(&self.assignee, &edited.assignee).map(|name| non_empty(&name)),
+            points: changed(&self.points, &edited.points),
+            due_date,
+            blocked: changed(&self.blocked, &edited.blocked),
+            blocked_note: changed(&self.blocked_note, &edited.blocked_note),
+        })
+    }
+}
+
+/// The fields of a user story read by [`TaigaAPI::story_fields`].
+#[derive(Debug, Deserialize)]
+struct EditableStory {
+    subject: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    assigned_to_extra_info: Option<UserInfo>,
+    #[serde(default)]
+    tags: Vec<(String, Option<String>)>,
+    /// Point value ID estimated by each role ID.
+    #[serde(default)]
+    points: HashMap<String, Option<usize>>,
+    #[serde(default)]
+    due_date: Option<String>,
+    #[serde(default)]
+    is_blocked: bool,
+    #[serde(default)]
+    blocked_note: String,
+}
+
+#[derive(Debug, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-role-detail>
+struct Role {
+    id: usize,
+    computable: bool,
+    order: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct StoryEpic {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    pub(super) subject: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CustomAttribute {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-userstory-custom-attributes-values-detail>
+struct CustomAttributesValues {
+    attributes_values: HashMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-attachment-detail>
+struct Attachment {
+    name: String,
+    size: u64,
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-history-entry>
+struct Comment {
+    user: CommentAuthor,
+    created_at: String,
+    comment: String,
+    #[serde(default, skip_serializing)]
+    delete_comment_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CommentAuthor {
+    username: String,
+    #[serde(default)]
+    name: String,
+}
+
+impl fmt::Display for StoryDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "📖 User Story #{} {}",
+            self.reference.bright_white().bold(),
+            self.subject.bold()
+        )?;
+        writeln!(f, "   Status:    {}", self.status_extra_info.name)?;
+
+        let assignee = self
+            .assigned_to_extra_info
+            .as_ref()
+            .map_or("Unassigned", |user| user.username.as_str());
+        writeln!(f, "   Assignee:  {assignee}")?;
+
+        if let Some(points) = self.total_points {
+            writeln!(f, "   Points:    {points}")?;
+        }
+        if let Some(sprint) = &self.milestone_name {
+            writeln!(f, "   Sprint:    {sprint}")?;
+        }
+        for epic in self.epics.iter().flatten() {
+            writeln!(f, "   Epic:      #{} {}", epic.reference, epic.subject)?;
+        }
+        if !self.tags.is_empty() {
+            let tags = self
+                .tags
+                .iter()
+                .map(|(tag, _)| tag.as_str())
+                .collect::<Vec<_>>();
+            writeln!(f, "   Tags:      {}", tags.join(", "))?;
+        }
+        if !self.watcher_names.is_empty() {
+            writeln!(f, "   Watchers:  {}", self.watcher_names.join(", "))?;
+        }
+        writeln!(f, "   Created:   {}", self.created_date)?;
+        writeln!(f, "   Modified:  {}", self.modified_date)?;
+
+        for attribute in &self.custom_attributes {
+            writeln!(f, "   {}: {}", attribute.name, attribute.value)?;
+        }
+
+        if let Some(description) = self.description.as_deref().filter(|d| !d.is_empty()) {
+            writeln!(f)?;
+            for line in markdown::render(description).lines() {
+                writeln!(f, "   {line}")?;
+            }
+        }
+
+        if !self.tasks.is_empty() {
+            writeln!(f, "\n☑️  Tasks ({})", self.tasks.len())?;
+            for task in &self.tasks {
+                writeln!(f, "  {task}")?;
+            }
+        }
+
+        if !self.attachments.is_empty() {
+            writeln!(f, "\n📎 Attachments ({})", self.attachments.len())?;
+            for attachment in &self.attachments {
+                writeln!(
+                    f,
+                    "  {} ({} KB) {}",
+                    attachment.name,
+                    attachment.size.div_ceil(1024),
+                    attachment.url.dimmed()
+                )?;
+                if !attachment.description.is_empty() {
+                    writeln!(f, "     {}", attachment.description)?;
+                }
+            }
+        }
+
+        if !self.comments.is_empty() {
+            writeln!(f, "\n💬 Latest comments")?;
+            for comment in &self.comments {
+                writeln!(
+                    f,
+                    "  {} · {}",
+                    comment.user.username.bold(),
+                    comment.created_at.dimmed()
+                )?;
+                for line in markdown::render(&comment.comment).lines() {
+                    writeln!(f, "     {line}")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor;
+
+    fn fields() -> StoryFields {
+        StoryFields {
+            subject: "Login".to_owned(),
+            assignee: "jdoe".to_owned(),
+            tags: vec!["auth".to_owned()],
+            points: "3".to_owned(),
+            due_date: "2025-07-18".to_owned(),
+            blocked: false,
+            blocked_note: String::new(),
+            description: "Fails on Safari.".to_owned(),
+            base: ItemVersion::default(),
+        }
+    }
+
+    #[test]
+    fn changes_are_empty_for_an_untouched_document() {
+        let original = fields();
+
+        let document = editor::to_document(&original, &original.description).unwrap();
+        let (mut edited, description): (StoryFields, String) =
+            editor::from_document(&document).unwrap();
+        edited.description = description;
+
+        assert!(original.changes(&edited).unwrap().is_empty());
+    }
+
+    #[test]
+    fn changes_hold_the_edited_fields_only() {
+        let original = fields();
+        let edited = StoryFields {
+            subject: "Login on Safari".to_owned(),
+            assignee: " ".to_owned(),
+            due_date: String::new(),
+            description: "Fails on Safari.\n".to_owned(),
+            ..fields()
+        };
+
+        let changes = original.changes(&edited).unwrap();
+
+        assert_eq!(changes.subject.as_deref(), Some("Login on Safari"));
+        assert_eq!(changes.assignee, Some(None));
+        assert_eq!(changes.due_date, Some(None));
+        assert_eq!(changes.description, None);
+        assert_eq!(changes.tags, None);
+        assert_eq!(changes.points, None);
+    }
+
+    #[test]
+    fn changes_reject_invalid_due_dates() {
+        let edited = StoryFields {
+            due_date: "next friday".to_owned(),
+            ..fields()
+        };
+
+        assert!(matches!(
+            fields().changes(&edited),
+            Err(TaigaAPIError::ValueNotFound(_))
+        ));
+    }
+}
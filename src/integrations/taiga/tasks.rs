@@ -0,0 +1,207 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use color_eyre::owo_colors::OwoColorize;
+
+use super::{Change, Resource, Status, StatusInfo, TaigaAPI, TaigaAPIError};
+use crate::progress;
+
+impl TaigaAPI {
+    /// Lists all tasks of the given project, optionally only the ones of a user story.
+    ///
+    /// `user_story_id` is the internal ID of the parent story, as returned by `get_story_id`.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails or the API response is invalid.
+    pub fn list_all_tasks(
+        &self,
+        project_id: usize,
+        user_story_id: Option<usize>,
+    ) -> Result<Vec<Task>, TaigaAPIError> {
+        let query = match user_story_id {
+            Some(user_story_id) => format!("project={project_id}&user_story={user_story_id}"),
+            None => format!("project={project_id}"),
+        };
+
+        let mut tasks: Vec<Task> = self.list_all(Resource::Task, &query)?;
+        for task in &mut tasks {
+            task.category = self.statuses.categorize(&task.status_extra_info);
+        }
+
+        Ok(tasks)
+    }
+
+    /// Creates a new task under the given user story and describes it as a change.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the request fails, status cannot be found,
+    /// or the API response is invalid.
+    pub fn create_task(
+        &self,
+        project_id: usize,
+        user_story_id: usize,
+        subject: &str,
+        description: &str,
+        status: &Status,
+    ) -> Result<Change, TaigaAPIError> {
+        let status_id = self.get_status_id(project_id, Resource::Task, status)?;
+
+        let payload = json!({
+            "project": project_id,
+            "user_story": user_story_id,
+            "subject": subject,
+            "description": description,
+            "status": status_id
+        });
+
+        self.create(Resource::Task, &payload)
+    }
+
+    /// Finds the internal task ID from a reference number within a given project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::TaskNotFound` if the task reference is not found.
+    pub fn get_task_id(&self, project_id: usize, task_ref: usize) -> Result<usize, TaigaAPIError> {
+        progress!("🔍 Looking up task with ref #{task_ref} in project...");
+
+        self.find_by_ref(Resource::Task, project_id, task_ref)?
+            .ok_or(TaigaAPIError::TaskNotFound(format!(
+                "Task with ref #{task_ref} not found."
+            )))
+    }
+
+    /// Updates the status of an existing task in the specified project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the status or task cannot be retrieved or updated.
+    pub fn update_task_status(
+        &self,
+        project_id: usize,
+        task_ref: usize,
+        task_id: usize,
+        status: &Status,
+    ) -> Result<Change, TaigaAPIError> {
+        progress!("🔍 Fetching '{status}' status ID for the project...");
+        let status = self.category_status(project_id, Resource::Task, status)?;
+
+        self.update_status(Resource::Task, task_ref, task_id, status)
+    }
+
+    /// Deletes a task with the given internal ID from the Taiga project.
+    ///
+    /// # Errors
+    /// Returns `TaigaAPIError::ApiError` if the deletion fails.
+    pub fn delete_task(&self, task_id: usize) -> Result<Change, TaigaAPIError> {
+        self.delete(Resource::Task, task_id)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// <https://docs.taiga.io/api.html#object-task-detail-list>
+pub struct Task {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+    status: usize,
+    created_date: String,
+    user_story: Option<usize>,
+    user_story_extra_info: Option<TaskUserStory>,
+    status_extra_info: StatusInfo,
+    /// What the status stands for, set by [`TaigaAPI::list_all_tasks`].
+    #[serde(skip)]
+    category: Option<Status>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TaskUserStory {
+    id: usize,
+    #[serde(rename = "ref")]
+    reference: usize,
+    subject: String,
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = match self.category {
+            Some(Status::Done) => self.reference.bright_green().bold().to_string(),
+            Some(Status::Wip) => self.reference.bright_yellow().bold().to_string(),
+            Some(Status::New) => self.reference.bright_blue().bold().to_string(),
+            None => self.reference.bright_white().bold().to_string(),
+        };
+
+        let story = self
+            .user_story_extra_info
+            .as_ref()
+            .map(|us| format!("(US #{})", us.reference))
+            .unwrap_or_default();
+
+        write!(
+            f,
+            "#{:>2} {:<40} {:<14} {}",
+            id,
+            self.subject,
+            self.status_extra_info.name,
+            story.dimmed()
+        )
+    }
+}
+
+pub struct Tasks(pub Vec<Task>);
+
+impl fmt::Display for Tasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📋 Total tasks: ({})\n", self.0.len())?;
+
+        for task in &self.0 {
+            writeln!(f, "  {task}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::integrations::taiga::StatusMapping;
+
+    fn task(status: &str, is_closed: bool) -> Task {
+        serde_json::from_value(json!({
+            "id": 7,
+            "ref": 42,
+            "subject": "Write the migration",
+            "status": 1,
+            "created_date": "2025-06-01T10:00:00Z",
+            "user_story": null,
+            "user_story_extra_info": null,
+            "status_extra_info": { "name": status, "color": "#999", "is_closed": is_closed },
+        }))
+        .unwrap()
+    }
+
+    fn colored_ref(status: &str, is_closed: bool, mapping: &StatusMapping) -> String {
+        let mut task = task(status, is_closed);
+        task.category = mapping.categorize(&task.status_extra_info);
+        task.to_string()
+    }
+
+    #[test]
+    fn display_colors_the_ref_after_the_status_mapping() {
+        let mapping = StatusMapping {
+            wip: vec!["En curso".to_owned()],
+            ..StatusMapping::default()
+        };
+
+        let wip = 42.bright_yellow().bold().to_string();
+        assert!(colored_ref("En curso", false, &mapping).contains(&wip));
+        // Tasks close as "Closed", not "Done", which only `is_closed` tells.
+        let done = 42.bright_green().bold().to_string();
+        assert!(colored_ref("Closed", true, &mapping).contains(&done));
+        let unknown = 42.bright_white().bold().to_string();
+        assert!(colored_ref("Needs info", false, &mapping).contains(&unknown));
+    }
+}
@@ -1,5 +1,15 @@
+pub mod cache;
 pub mod cli;
+pub mod config;
+pub mod credentials;
+pub mod dates;
+pub mod editor;
+pub mod git;
+pub mod hooks;
+pub mod import;
 pub mod integrations;
+pub mod markdown;
+pub mod output;
 
 pub trait ExitOnError<T> {
     fn or_exit(self) -> T;
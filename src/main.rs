@@ -1,78 +1,993 @@
 use backlogr::{
-    cli::{Cli, Command},
-    integrations::taiga::{Status, TaigaAPI, UserStories},
-    ExitOnError,
+    cache::RefCache,
+    cli::{
+        Cli, Command, EpicCommand, Format, HooksCommand, IssueCommand, SprintCommand,
+        StorySelection, TaskCommand,
+    },
+    config::Settings,
+    credentials::{Credentials, Session},
+    dates::Date,
+    editor,
+    git::{self, Commit, CommitRules},
+    hooks::{self, ClosedPolicy},
+    import,
+    integrations::taiga::{
+        idempotency_tag, BatchReport, Change, ChangeAction, Epics, IssueChanges, Selection,
+        Sprints, Status, StoryChanges, StoryFields, TaigaAPI, TaigaAPIError, Tasks, UserStories,
+    },
+    output::{self, Template},
+    progress, ExitOnError,
 };
 use clap::Parser;
 use color_eyre::owo_colors::OwoColorize;
 use eyre::Result;
+use std::{
+    fs,
+    io::{self, Read},
+};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
     let cli = Cli::parse();
-    let username = cli.username.clone();
-    let password = cli.password.clone();
-    let project_name = cli.project_name.clone();
+    output::set_quiet(cli.quiet);
+    let settings = Settings::resolve(&cli)?;
+
+    match cli.command() {
+        Command::Login => return login(&settings),
+        Command::Logout => return logout(&settings),
+        Command::Hooks {
+            command: HooksCommand::Install { closed, force },
+        } => return install_hooks(closed, force),
+        _ => {}
+    }
 
-    let taiga_api = TaigaAPI::authenticate(&username, &password).or_exit();
+    let taiga_api = connect(&settings)?
+        .with_status_mapping(settings.statuses.clone())
+        .with_ref_cache(RefCache::load());
 
-    let project_id = taiga_api.get_project_id(&project_name).or_exit();
+    let project_name = settings.project_name()?;
+    let project_id = taiga_api.get_project_id(project_name).or_exit();
 
     match cli.command() {
         Command::Create {
             subject,
             description,
             status,
+            if_not_exists,
+            idempotency_key,
         } => {
+            if if_not_exists || idempotency_key.is_some() {
+                let existing = taiga_api
+                    .find_created_story(project_id, &subject, idempotency_key.as_deref())
+                    .or_exit();
+
+                if let Some(change) = existing {
+                    let line = format!(
+                        "♻️ Story already exists: \"{subject}\" (#{})",
+                        change.reference.unwrap_or_default().bold().bright_green()
+                    );
+                    return print_change(&settings.format, &change, &line);
+                }
+            }
+
+            let tags = idempotency_key
+                .as_deref()
+                .map(idempotency_tag)
+                .into_iter()
+                .collect::<Vec<_>>();
+
             let description = description.unwrap_or_default();
-            let story_id = taiga_api
-                .create_story(project_id, &subject, &description, &status)
+            let change = taiga_api
+                .create_story(project_id, &subject, &description, &status, &tags)
                 .or_exit();
 
-            eprintln!(
+            let line = format!(
                 "✅ Created story: \"{subject}\" (#{})",
-                story_id.bold().bright_green()
+                change.reference.unwrap_or_default().bold().bright_green()
             );
+            print_change(&settings.format, &change, &line)?;
         }
-        Command::Wip { story_id } => {
-            let real_id = taiga_api.get_story_id(project_id, story_id).or_exit();
+        Command::Wip { stories } => {
+            let selection = select_stories(&taiga_api, project_id, &stories);
 
-            taiga_api
-                .update_story_status(project_id, story_id, real_id, &Status::Wip)
+            let report = taiga_api
+                .set_stories_status(project_id, &selection, &Status::Wip)
                 .or_exit();
+
+            print_batch_report(&settings.format, &report)?;
         }
-        Command::Done { story_id } => {
-            let real_id = taiga_api.get_story_id(project_id, story_id).or_exit();
+        Command::Done { stories } => {
+            let selection = select_stories(&taiga_api, project_id, &stories);
 
-            taiga_api
-                .update_story_status(project_id, story_id, real_id, &Status::Done)
+            let report = taiga_api
+                .set_stories_status(project_id, &selection, &Status::Done)
                 .or_exit();
+
+            print_batch_report(&settings.format, &report)?;
         }
-        Command::Delete { story_id } => {
+        Command::Delete { stories } => {
+            let selection = select_stories(&taiga_api, project_id, &stories);
+
+            let report = taiga_api.delete_stories(&selection);
+
+            print_batch_report(&settings.format, &report)?;
+        }
+        Command::Show { story_id } => {
             let real_id = taiga_api.get_story_id(project_id, story_id).or_exit();
 
-            taiga_api.delete_story(real_id).or_exit();
+            let story = taiga_api.get_story(project_id, real_id).or_exit();
 
-            eprintln!(
-                "✅ Successfully deleted user story (#{})",
-                story_id.bold().bright_green(),
-            );
+            match settings.format {
+                Format::Pretty => eprintln!("{story}"),
+                format => output::print(&format, &story)?,
+            }
+        }
+        Command::Edit {
+            story_id,
+            subject,
+            description,
+            tag,
+            no_tags,
+            assignee,
+            unassign,
+            points,
+            due_date,
+            no_due_date,
+            blocked,
+            unblock,
+        } => {
+            let real_id = taiga_api.get_story_id(project_id, story_id).or_exit();
+
+            let changes = StoryChanges {
+                subject,
+                description,
+                tags: (no_tags || !tag.is_empty()).then_some(tag),
+                assignee: if unassign {
+                    Some(None)
+                } else {
+                    assignee.map(Some)
+                },
+                points,
+                due_date: if no_due_date {
+                    Some(None)
+                } else {
+                    due_date.map(Some)
+                },
+                blocked: (unblock || blocked.is_some()).then_some(!unblock),
+                blocked_note: if unblock {
+                    Some(String::new())
+                } else {
+                    blocked.filter(|note| !note.is_empty())
+                },
+            };
+
+            let change = if changes.is_empty() {
+                let original = taiga_api.story_fields(project_id, real_id).or_exit();
+                let document = editor::to_document(&original, &original.description)?;
+
+                let text = editor::edit(&document, &format!("story-{story_id}.md"))?;
+                let (mut edited, description): (StoryFields, String) =
+                    editor::from_document(&text)?;
+                edited.description = description;
+
+                taiga_api
+                    .save_story_fields(project_id, real_id, &original, &edited)
+                    .or_exit()
+            } else {
+                taiga_api
+                    .edit_story(project_id, real_id, &changes)
+                    .or_exit()
+            };
+
+            let line = match change.action {
+                ChangeAction::Unchanged => format!("🤷 No changes to user story (#{story_id})"),
+                _ => format!(
+                    "✅ Updated user story (#{}) (version {})",
+                    story_id.bold().bright_green(),
+                    change.version.unwrap_or_default()
+                ),
+            };
+            print_change(&settings.format, &change, &line)?;
         }
-        Command::List { format } => {
-            let stories = taiga_api.list_all_stories(project_id).or_exit();
+        Command::Move {
+            story_id,
+            to,
+            sprint,
+        } => {
+            let real_id = taiga_api.get_story_id(project_id, story_id).or_exit();
+
+            let mut lines = Vec::new();
+            let mut moved: Option<Change> = None;
+
+            if let Some(status) = to {
+                let change = taiga_api
+                    .move_story(project_id, story_id, real_id, &status)
+                    .or_exit();
+
+                lines.push(status_line(&change));
+                moved = Some(change);
+            }
+
+            if let Some(sprint) = sprint {
+                let sprint_id = taiga_api.get_sprint_id(project_id, &sprint).or_exit();
 
-            match format {
-                backlogr::cli::Format::Pretty => {
-                    let user_stories = UserStories::new(stories);
+                let mut change = taiga_api.move_story_to_sprint(real_id, sprint_id).or_exit();
+
+                lines.push(format!(
+                    "✅ Moved user story (#{}) to sprint '{sprint}'",
+                    story_id.bold().bright_green(),
+                ));
+                // Both updates make up a single change of the story.
+                if let Some(first) = moved {
+                    change.old_status = first.old_status;
+                }
+                moved = Some(change);
+            }
+
+            if let Some(change) = moved {
+                print_change(&settings.format, &change, &lines.join("\n"))?;
+            }
+        }
+        Command::List {
+            filter,
+            sort,
+            columns,
+            template,
+            template_file,
+        } => {
+            let template = match (template, template_file) {
+                (Some(source), _) => Some(Template::inline(&source)),
+                (None, Some(path)) => Some(Template::load(&path)?),
+                (None, None) => None,
+            };
+
+            let mut stories = taiga_api.list_stories(project_id, &filter).or_exit();
+
+            if let Some(sort) = sort {
+                sort.sort(&mut stories);
+            }
+
+            match (template, settings.format) {
+                (Some(template), _) => print!("{}", template.render(&stories)?),
+                (None, Format::Pretty) => {
+                    let user_stories = UserStories::new(stories).with_columns(columns);
 
                     eprintln!("{user_stories}");
                 }
-                backlogr::cli::Format::Json => {
-                    println!("{}", serde_json::to_string_pretty(&stories)?);
+                (None, format) => output::print(&format, &stories)?,
+            }
+        }
+        Command::FromCommit {
+            file,
+            range,
+            sha,
+            dry_run,
+        } => {
+            let commits = match (range, file) {
+                (Some(range), _) => git::commits(&[&range])?,
+                (None, Some(path)) => vec![Commit {
+                    sha,
+                    message: git::strip_comments(&fs::read_to_string(path)?),
+                }],
+                (None, None) => {
+                    let mut message = String::new();
+                    io::stdin().read_to_string(&mut message)?;
+                    vec![Commit {
+                        sha,
+                        message: git::strip_comments(&message),
+                    }]
+                }
+            };
+
+            apply_commits(
+                &taiga_api,
+                project_id,
+                &settings.commits,
+                &settings.format,
+                &commits,
+                dry_run,
+            )?;
+        }
+        Command::Import { file, dry_run } => {
+            let stories = import::read_stories(&file)?;
+
+            progress!(
+                "🔍 Checking {} stories from {}...",
+                stories.len(),
+                file.display()
+            );
+
+            let report = taiga_api.import_stories(project_id, &stories, dry_run);
+
+            match settings.format {
+                Format::Pretty => eprintln!("{report}"),
+                format => output::print(&format, &report)?,
+            }
+
+            if report.count_invalid() > 0 && !dry_run {
+                eprintln!("❌ Nothing was imported, fix the invalid rows first");
+            }
+            if report.has_errors() {
+                std::process::exit(1);
+            }
+        }
+        Command::Plan { file } => {
+            let backlog = import::read_backlog(&file)?;
+
+            let plan = taiga_api.plan_backlog(project_id, &backlog).or_exit();
+
+            match settings.format {
+                Format::Pretty => eprintln!("{plan}"),
+                format => output::print(&format, &plan)?,
+            }
+
+            if !plan.is_empty() {
+                eprintln!(
+                    "💡 Run `backlogr apply {}` to make these changes",
+                    file.display()
+                );
+            }
+        }
+        Command::Apply { file } => {
+            let backlog = import::read_backlog(&file)?;
+
+            let plan = taiga_api.plan_backlog(project_id, &backlog).or_exit();
+            let plan = taiga_api.apply_plan(project_id, plan);
+
+            match settings.format {
+                Format::Pretty => eprintln!("{plan}"),
+                format => output::print(&format, &plan)?,
+            }
+
+            if let Some(code) = plan.exit_code() {
+                std::process::exit(code);
+            }
+        }
+        Command::Changelog {
+            range,
+            since,
+            until,
+            group_by,
+            style,
+            release,
+            include_open,
+        } => {
+            // Dates are compared from midnight, so `--until` excludes the day itself.
+            let mut revisions = Vec::new();
+            revisions.extend(range);
+            revisions.extend(since.map(|date| format!("--since={date}T00:00:00")));
+            revisions.extend(until.map(|date| format!("--until={date}T00:00:00")));
+
+            let commits = git::commits(&revisions.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+            let changelog = taiga_api
+                .changelog(project_id, &commits, group_by, include_open, release)
+                .or_exit();
+
+            for reference in changelog.unknown_refs() {
+                eprintln!("⚠️ #{reference} is mentioned in the commits but is not a story of {project_name}");
+            }
+
+            match settings.format {
+                Format::Pretty | Format::Markdown => print!("{}", changelog.render(style)),
+                format => output::print(&format, &changelog)?,
+            }
+        }
+        Command::Hooks { command } => match command {
+            HooksCommand::Install { .. } => unreachable!("handled before connecting"),
+            HooksCommand::Check {
+                file,
+                range,
+                pre_push,
+                closed,
+            } => {
+                let commits = if pre_push {
+                    let mut lines = String::new();
+                    io::stdin().read_to_string(&mut lines)?;
+                    hooks::pushed_commits(&lines)?
+                } else if let Some(range) = range {
+                    git::commits(&[&range])?
+                } else if let Some(path) = file {
+                    vec![Commit {
+                        sha: None,
+                        message: git::strip_comments(&fs::read_to_string(path)?),
+                    }]
+                } else {
+                    let mut message = String::new();
+                    io::stdin().read_to_string(&mut message)?;
+                    vec![Commit {
+                        sha: None,
+                        message: git::strip_comments(&message),
+                    }]
+                };
+
+                check_refs(&taiga_api, project_id, project_name, &commits, closed);
+            }
+        },
+        Command::Task { command } => match command {
+            TaskCommand::Create {
+                story,
+                subject,
+                description,
+                status,
+            } => {
+                let story_id = taiga_api.get_story_id(project_id, story).or_exit();
+
+                let description = description.unwrap_or_default();
+                let change = taiga_api
+                    .create_task(project_id, story_id, &subject, &description, &status)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Created task: \"{subject}\" (#{}) in user story #{story}",
+                    change.reference.unwrap_or_default().bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            TaskCommand::Wip { task_id } => {
+                let real_id = taiga_api.get_task_id(project_id, task_id).or_exit();
+
+                let change = taiga_api
+                    .update_task_status(project_id, task_id, real_id, &Status::Wip)
+                    .or_exit();
+
+                print_change(&settings.format, &change, &status_line(&change))?;
+            }
+            TaskCommand::Done { task_id } => {
+                let real_id = taiga_api.get_task_id(project_id, task_id).or_exit();
+
+                let change = taiga_api
+                    .update_task_status(project_id, task_id, real_id, &Status::Done)
+                    .or_exit();
+
+                print_change(&settings.format, &change, &status_line(&change))?;
+            }
+            TaskCommand::Delete { task_id } => {
+                let real_id = taiga_api.get_task_id(project_id, task_id).or_exit();
+
+                let change = taiga_api.delete_task(real_id).or_exit();
+
+                let line = format!(
+                    "✅ Successfully deleted task (#{})",
+                    task_id.bold().bright_green(),
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            TaskCommand::List { story } => {
+                let story_id = story
+                    .map(|story| taiga_api.get_story_id(project_id, story))
+                    .transpose()
+                    .or_exit();
+
+                let tasks = taiga_api.list_all_tasks(project_id, story_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{}", Tasks(tasks)),
+                    format => output::print(&format, &tasks)?,
+                }
+            }
+        },
+        Command::Issue { command } => match command {
+            IssueCommand::Create {
+                subject,
+                description,
+                issue_type,
+                severity,
+                priority,
+            } => {
+                let description = description.unwrap_or_default();
+                let change = taiga_api
+                    .create_issue(
+                        project_id,
+                        &subject,
+                        &description,
+                        issue_type.as_ref(),
+                        severity.as_ref(),
+                        priority.as_ref(),
+                    )
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Created issue: \"{subject}\" (#{})",
+                    change.reference.unwrap_or_default().bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            IssueCommand::List => {
+                let issues = taiga_api.list_all_issues(project_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{issues}"),
+                    format => output::print(&format, &issues)?,
+                }
+            }
+            IssueCommand::Show { issue_id } => {
+                let real_id = taiga_api.get_issue_id(project_id, issue_id).or_exit();
+                let issue = taiga_api.get_issue(project_id, real_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{issue}"),
+                    format => output::print(&format, &issue)?,
+                }
+            }
+            IssueCommand::Update {
+                issue_id,
+                subject,
+                description,
+                issue_type,
+                severity,
+                priority,
+                status,
+            } => {
+                let real_id = taiga_api.get_issue_id(project_id, issue_id).or_exit();
+
+                let changes = IssueChanges {
+                    subject,
+                    description,
+                    issue_type,
+                    severity,
+                    priority,
+                    status,
+                };
+                let change = taiga_api
+                    .update_issue(project_id, real_id, &changes)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Successfully updated issue (#{}) (version {})",
+                    issue_id.bold().bright_green(),
+                    change.version.unwrap_or_default()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            IssueCommand::Close { issue_id } => {
+                let real_id = taiga_api.get_issue_id(project_id, issue_id).or_exit();
+
+                let changes = IssueChanges {
+                    status: Some(Status::Done),
+                    ..IssueChanges::default()
+                };
+                let change = taiga_api
+                    .update_issue(project_id, real_id, &changes)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Successfully closed issue (#{})",
+                    issue_id.bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            IssueCommand::Delete { issue_id } => {
+                let real_id = taiga_api.get_issue_id(project_id, issue_id).or_exit();
+
+                let change = taiga_api.delete_issue(real_id).or_exit();
+
+                let line = format!(
+                    "✅ Successfully deleted issue (#{})",
+                    issue_id.bold().bright_green(),
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+        },
+        Command::Epic { command } => match command {
+            EpicCommand::Create {
+                subject,
+                description,
+                status,
+            } => {
+                let description = description.unwrap_or_default();
+                let change = taiga_api
+                    .create_epic(project_id, &subject, &description, &status)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Created epic: \"{subject}\" (#{})",
+                    change.reference.unwrap_or_default().bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            EpicCommand::List => {
+                let epics = taiga_api.list_all_epics(project_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{}", Epics(epics)),
+                    format => output::print(&format, &epics)?,
+                }
+            }
+            EpicCommand::Show { epic_id } => {
+                let real_id = taiga_api.get_epic_id(project_id, epic_id).or_exit();
+                let progress = taiga_api.get_epic_progress(project_id, real_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{progress}"),
+                    format => output::print(&format, &progress)?,
+                }
+            }
+            EpicCommand::Link { epic_id, story_id } => {
+                let real_epic_id = taiga_api.get_epic_id(project_id, epic_id).or_exit();
+                let real_story_id = taiga_api.get_story_id(project_id, story_id).or_exit();
+
+                let change = taiga_api
+                    .link_story_to_epic(real_epic_id, real_story_id)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Linked user story #{} to epic #{}",
+                    story_id.bold().bright_green(),
+                    epic_id.bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            EpicCommand::Unlink { epic_id, story_id } => {
+                let real_epic_id = taiga_api.get_epic_id(project_id, epic_id).or_exit();
+                let real_story_id = taiga_api.get_story_id(project_id, story_id).or_exit();
+
+                let change = taiga_api
+                    .unlink_story_from_epic(real_epic_id, real_story_id)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Unlinked user story #{} from epic #{}",
+                    story_id.bold().bright_green(),
+                    epic_id.bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+        },
+        Command::Sprint { command } => match command {
+            SprintCommand::List => {
+                let sprints = taiga_api.list_sprints(project_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{}", Sprints(sprints)),
+                    format => output::print(&format, &sprints)?,
+                }
+            }
+            SprintCommand::Create {
+                name,
+                start,
+                finish,
+            } => {
+                let start = start.unwrap_or_else(Date::today);
+                let finish = finish.unwrap_or_else(|| start.add_days(13));
+
+                let change = taiga_api
+                    .create_sprint(project_id, &name, start, finish)
+                    .or_exit();
+
+                let line = format!(
+                    "✅ Created sprint: 🏃 {} {start} → {finish}",
+                    name.bold().bright_green()
+                );
+                print_change(&settings.format, &change, &line)?;
+            }
+            SprintCommand::Show { name } => {
+                let sprint_id = match name {
+                    Some(name) => taiga_api.get_sprint_id(project_id, &name).or_exit(),
+                    None => taiga_api.get_current_sprint(project_id).or_exit().id(),
+                };
+
+                let sprint = taiga_api.get_sprint(sprint_id).or_exit();
+
+                match settings.format {
+                    Format::Pretty => eprintln!("{sprint}"),
+                    format => output::print(&format, &sprint)?,
+                }
+            }
+            SprintCommand::Close { name } => {
+                let sprint_id = taiga_api.get_sprint_id(project_id, &name).or_exit();
+
+                let change = taiga_api.close_sprint(sprint_id).or_exit();
+
+                let line = match change.action {
+                    ChangeAction::Unchanged => format!("➖ Sprint '{name}' is already closed"),
+                    _ => format!("✅ Closed sprint '{}'", name.bold().bright_green()),
+                };
+                print_change(&settings.format, &change, &line)?;
+            }
+        },
+        Command::Login | Command::Logout => unreachable!("handled before connecting"),
+    }
+    Ok(())
+}
+
+/// Authenticates with the password if one is given explicitly, otherwise reuses the session
+/// stored by `login` for the configured user, and only falls back to the password of the
+/// profile without one.
+fn connect(settings: &Settings) -> Result<TaigaAPI> {
+    let api_url = settings.api_url.as_str();
+
+    if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+        return Ok(TaigaAPI::authenticate(api_url, username, password).or_exit());
+    }
+
+    let credentials = Credentials::load()?;
+    let stored = credentials.get(api_url);
+    if let Some(session) = stored.filter(|session| session.belongs_to(settings.username.as_deref()))
+    {
+        progress!(
+            "🔐 Using stored session for {}",
+            session.username.bold().cyan()
+        );
+
+        let url = api_url.to_owned();
+        return Ok(
+            TaigaAPI::from_tokens(api_url, session.tokens.clone()).on_refresh(move |tokens| {
+                if let Err(err) = Credentials::update_tokens(&url, tokens) {
+                    eprintln!("⚠️ Could not store the refreshed session: {err}");
+                }
+            }),
+        );
+    }
+
+    let (Some(username), Some(password)) = (&settings.username, settings.login_password()?) else {
+        let reason = match stored {
+            Some(session) => format!(
+                "the session stored for {api_url} belongs to {}",
+                session.username
+            ),
+            None => format!("no stored session for {api_url}"),
+        };
+        TaigaAPIError::Authentication(format!("No password given and {reason}.")).exit_with_tips()
+    };
+
+    Ok(TaigaAPI::authenticate(api_url, username, &password).or_exit())
+}
+
+fn login(settings: &Settings) -> Result<()> {
+    let api_url = settings.api_url.as_str();
+
+    let (Some(username), Some(password)) = (&settings.username, settings.login_password()?) else {
+        TaigaAPIError::Authentication(
+            "`backlogr login` needs a username and a password.".to_owned(),
+        )
+        .exit_with_tips()
+    };
+
+    let taiga_api = TaigaAPI::authenticate(api_url, username, &password).or_exit();
+
+    let mut credentials = Credentials::load()?;
+    credentials.insert(
+        api_url,
+        Session {
+            username: username.clone(),
+            tokens: taiga_api.tokens(),
+        },
+    );
+    let path = credentials.save()?;
+
+    eprintln!(
+        "✅ Logged in as {} (session stored in {})",
+        username.bold().bright_green(),
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Moves the stories referenced by each commit, in order, and comments on them.
+///
+/// A failure on one story does not stop the others; the first one is reported at the end, after
+/// the changes made are printed in `format`.
+fn apply_commits(
+    taiga_api: &TaigaAPI,
+    project_id: usize,
+    rules: &CommitRules,
+    format: &Format,
+    commits: &[Commit],
+    dry_run: bool,
+) -> Result<()> {
+    let mut failure = None;
+    let mut found = false;
+    let mut changes = Vec::new();
+
+    for commit in commits {
+        let label = commit.short_sha().unwrap_or("message");
+
+        for action in rules.actions(&commit.message) {
+            found = true;
+            let reference = action.reference;
+
+            if dry_run {
+                eprintln!(
+                    "📝 {label}: '{}' would move #{reference} to '{}'",
+                    action.keyword, action.status
+                );
+                continue;
+            }
+
+            let result = taiga_api
+                .get_story_id(project_id, reference)
+                .and_then(|id| {
+                    taiga_api.transition_story(
+                        project_id,
+                        id,
+                        &action.status,
+                        &rules.comment(commit),
+                    )
+                });
+
+            match result {
+                Ok(change) => {
+                    let from = change.old_status.as_deref().unwrap_or_default();
+                    let to = change.new_status.as_deref().unwrap_or_default();
+
+                    match (format, change.action) {
+                        (Format::Pretty, ChangeAction::Unchanged) => {
+                            eprintln!("⏭️ {label}: #{reference} is already in '{to}', skipped");
+                        }
+                        (Format::Pretty, _) => eprintln!(
+                            "✅ {label}: moved #{} from '{from}' to '{to}'",
+                            reference.bold().bright_green()
+                        ),
+                        _ => {}
+                    }
+                    changes.push(change);
+                }
+                Err(err) => {
+                    eprintln!("❌ {label}: #{reference}: {err}");
+                    failure.get_or_insert(err);
+                }
+            }
+        }
+    }
+
+    if !found {
+        eprintln!("ℹ️ No story refs with a known keyword found, nothing to do");
+    }
+
+    if !matches!(format, Format::Pretty) && !dry_run {
+        output::print(format, &changes)?;
+    }
+
+    if let Some(err) = failure {
+        err.exit_with_tips();
+    }
+
+    Ok(())
+}
+
+/// Prints the change made by a command: `line` in the pretty format, the change itself on stdout
+/// in the others.
+fn print_change(format: &Format, change: &Change, line: &str) -> Result<()> {
+    match format {
+        Format::Pretty => eprintln!("{line}"),
+        format => output::print(format, change)?,
+    }
+
+    Ok(())
+}
+
+/// Line telling the status an item was moved to.
+fn status_line(change: &Change) -> String {
+    let status = change.new_status.as_deref().unwrap_or_default();
+
+    match change.action {
+        ChangeAction::Unchanged => format!(
+            "➖ {} #{} is already in '{status}'",
+            change.kind.replace('_', " "),
+            change.reference.unwrap_or_default()
+        ),
+        _ => format!(
+            "✅ Successfully updated {} #{} to '{}' (version {})",
+            change.kind.replace('_', " "),
+            change.reference.unwrap_or_default().bold().bright_green(),
+            status.bright_green(),
+            change.version.unwrap_or_default()
+        ),
+    }
+}
+
+/// Checks that every story ref mentioned in `commits` exists and, per `closed`, is open.
+///
+/// Exits with an error if any ref is wrong, so the hook running it rejects the commit or push.
+fn check_refs(
+    taiga_api: &TaigaAPI,
+    project_id: usize,
+    project_name: &str,
+    commits: &[Commit],
+    closed: ClosedPolicy,
+) {
+    let mut checked = Vec::new();
+    let mut problems = 0;
+
+    for commit in commits {
+        for reference in git::mentioned_refs(&commit.message) {
+            if checked.contains(&reference) {
+                continue;
+            }
+            checked.push(reference);
+
+            let story = match taiga_api.get_story_id(project_id, reference) {
+                Ok(id) => taiga_api.get_story_status(id).or_exit(),
+                Err(TaigaAPIError::StoryNotFound(_)) => {
+                    eprintln!("❌ #{reference} does not exist in {project_name}");
+                    problems += 1;
+                    continue;
+                }
+                Err(err) => err.exit_with_tips(),
+            };
+
+            let status = &story.status.name;
+            match (story.status.is_closed, closed) {
+                (false, _) => eprintln!("✅ #{reference} {} ({status})", story.subject),
+                (true, ClosedPolicy::Warn) => {
+                    eprintln!(
+                        "⚠️ #{reference} {} is already closed ({status})",
+                        story.subject
+                    );
+                }
+                (true, ClosedPolicy::Fail) => {
+                    eprintln!(
+                        "❌ #{reference} {} is already closed ({status})",
+                        story.subject
+                    );
+                    problems += 1;
                 }
             }
         }
     }
+
+    if problems > 0 {
+        eprintln!(
+            "💡 Fix the story refs, or skip the check once with `git commit/push --no-verify`"
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Finds the stories given on the command line, exiting early if there are none.
+fn select_stories(taiga_api: &TaigaAPI, project_id: usize, stories: &StorySelection) -> Selection {
+    let selection = taiga_api
+        .select_stories(project_id, &stories.refs(), stories.filter.as_ref())
+        .or_exit();
+
+    if selection.is_empty() {
+        eprintln!("⚠️ No user story matches the filter, nothing to do");
+        std::process::exit(0);
+    }
+
+    selection
+}
+
+/// Prints the outcome of a change to several stories, exiting with the code of the first
+/// failure if any story failed.
+fn print_batch_report(format: &Format, report: &BatchReport) -> Result<()> {
+    match format {
+        Format::Pretty => eprintln!("{report}"),
+        format => output::print(format, report)?,
+    }
+
+    if let Some(code) = report.exit_code() {
+        std::process::exit(code);
+    }
+
+    Ok(())
+}
+
+fn install_hooks(closed: ClosedPolicy, force: bool) -> Result<()> {
+    for path in hooks::install(closed, force)? {
+        eprintln!("✅ Installed {}", path.display().bold().bright_green());
+    }
+
+    Ok(())
+}
+
+fn logout(settings: &Settings) -> Result<()> {
+    let api_url = settings.api_url.as_str();
+
+    let mut credentials = Credentials::load()?;
+    match credentials.remove(api_url) {
+        Some(session) => {
+            credentials.save()?;
+            eprintln!(
+                "✅ Logged out {} from {api_url}",
+                session.username.bold().bright_green()
+            );
+        }
+        None => eprintln!("ℹ️ No stored session for {api_url}"),
+    }
+
     Ok(())
 }
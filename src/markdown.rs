@@ -0,0 +1,163 @@
+use color_eyre::owo_colors::OwoColorize;
+
+/// Renders the Markdown of descriptions and comments for the terminal.
+///
+/// Only what Taiga users commonly write is handled: headings, lists, quotes, code blocks,
+/// emphasis, inline code and links. Anything else is printed as is.
+pub fn render(text: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            lines.push(format!("  {}", line.dimmed()));
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+
+        let rendered = if let Some(heading) = heading(trimmed) {
+            inline(heading).bold().underline().to_string()
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            format!("{indent}• {}", inline(item))
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            format!(
+                "{indent}{} {}",
+                "│".dimmed(),
+                inline(quote.trim_start()).italic()
+            )
+        } else {
+            format!("{indent}{}", inline(trimmed))
+        };
+
+        lines.push(rendered);
+    }
+
+    lines.join("\n")
+}
+
+fn heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+
+    if (1..=6).contains(&level) {
+        line[level..].strip_prefix(' ')
+    } else {
+        None
+    }
+}
+
+/// Renders emphasis, inline code and links within a line.
+fn inline(line: &str) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((code, after)) = delimited(rest, "`") {
+            out.push_str(&code.cyan().to_string());
+            rest = after;
+            continue;
+        }
+
+        if let Some((text, after)) = delimited(rest, "**").or_else(|| delimited(rest, "__")) {
+            out.push_str(&inline(text).bold().to_string());
+            rest = after;
+            continue;
+        }
+
+        if let Some((text, after)) = delimited(rest, "*") {
+            out.push_str(&inline(text).italic().to_string());
+            rest = after;
+            continue;
+        }
+
+        if let Some((text, url, after)) = link(rest) {
+            out.push_str(&format!("{} ({})", inline(text), url.underline()));
+            rest = after;
+            continue;
+        }
+
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+/// Splits `marker text marker rest` into `(text, rest)` when `s` starts with it.
+fn delimited<'a>(s: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    let inner = s.strip_prefix(marker)?;
+    let end = inner.find(marker)?;
+
+    (end > 0).then(|| (&inner[..end], &inner[end + marker.len()..]))
+}
+
+/// Splits `[text](url) rest` into `(text, url, rest)` when `s` starts with a link.
+fn link(s: &str) -> Option<(&str, &str, &str)> {
+    let inner = s.strip_prefix('[')?;
+    let close = inner.find("](")?;
+    let target = &inner[close + 2..];
+    let end = target.find(')')?;
+
+    Some((&inner[..close], &target[..end], &target[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` without its ANSI styles.
+    fn plain(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn blocks_are_marked_and_code_is_left_as_is() {
+        let text = "## Steps\n- Open *Safari*\n  * Log in\n> It hangs\n```\n**not bold**\n```";
+
+        assert_eq!(
+            plain(&render(text)),
+            "Steps\n• Open Safari\n  • Log in\n│ It hangs\n  **not bold**"
+        );
+    }
+
+    #[test]
+    fn headings_need_a_space_after_at_most_six_hashes() {
+        assert_eq!(heading("### Notes"), Some("Notes"));
+        assert_eq!(heading("#42 is blocked"), None);
+        assert_eq!(heading("####### Too deep"), None);
+    }
+
+    #[test]
+    fn links_keep_their_url() {
+        assert_eq!(
+            plain(&inline("See [the spec](https://example.com/spec).")),
+            "See the spec (https://example.com/spec)."
+        );
+    }
+
+    #[test]
+    fn unclosed_markers_are_printed_as_is() {
+        assert_eq!(plain(&inline("2 * 3 and `x")), "2 * 3 and `x");
+        assert_eq!(delimited("****", "**"), None);
+    }
+}
@@ -0,0 +1,438 @@
+use minijinja::Environment;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    cli::Format,
+    integrations::taiga::{ListingView, UserStory},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum OutputError {
+    #[error("Failed to serialize the output as JSON")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to serialize the output as YAML")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Failed to write the output as CSV")]
+    Csv(#[from] csv::Error),
+    #[error("Failed to write the output")]
+    Io(#[from] io::Error),
+    #[error("Failed to read template file {}", path.display())]
+    TemplateFile { path: PathBuf, source: io::Error },
+    #[error("Invalid template")]
+    Template(#[from] minijinja::Error),
+}
+
+/// Whether progress lines are left out, set once from `--quiet`.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints a line about what a command is doing to stderr, unless `--quiet` is set.
+///
+/// Results and errors are printed with `eprintln!` or [`print`] instead, so that they are
+/// never silenced.
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Field Taiga uses for the name of the status of stories, tasks, issues and epics.
+const STATUS_FIELD: &str = "status_extra_info.name";
+
+/// Prints the result of a command to stdout in a machine-readable `format`.
+///
+/// `Format::Pretty` is rendered by each command through the `Display` impl of its result;
+/// when it reaches this function it is treated as `Format::Json`.
+///
+/// Lists are written one record per row (CSV, Markdown) or per line (NDJSON). Nested fields
+/// become dotted columns such as `status_extra_info.name`.
+///
+/// # Errors
+/// Returns `OutputError` if the data cannot be serialized or stdout cannot be written.
+pub fn print<T: Serialize>(format: &Format, data: &T) -> Result<(), OutputError> {
+    let mut stdout = io::stdout().lock();
+
+    match format {
+        Format::Pretty | Format::Json => {
+            writeln!(stdout, "{}", serde_json::to_string_pretty(data)?)?;
+        }
+        Format::Ndjson => match serde_json::to_value(data)? {
+            Value::Array(items) => {
+                for item in items {
+                    writeln!(stdout, "{}", serde_json::to_string(&item)?)?;
+                }
+            }
+            value => writeln!(stdout, "{}", serde_json::to_string(&value)?)?,
+        },
+        Format::Yaml => write!(stdout, "{}", serde_yaml::to_string(data)?)?,
+        Format::Csv => {
+            let table = Table::new(&serde_json::to_value(data)?);
+            if table.headers.is_empty() {
+                return Ok(());
+            }
+
+            let mut writer = csv::Writer::from_writer(stdout);
+            writer.write_record(&table.headers)?;
+            for row in &table.rows {
+                writer.write_record(row)?;
+            }
+            writer.flush()?;
+        }
+        Format::Markdown => {
+            let table = Table::new(&serde_json::to_value(data)?);
+            write!(stdout, "{}", table.markdown())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Records flattened into rows of text, with the union of their fields as headers.
+struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new(value: &Value) -> Self {
+        let records = match value {
+            Value::Array(items) => items.iter().map(flatten).collect(),
+            value => vec![flatten(value)],
+        };
+
+        let mut headers: Vec<String> = Vec::new();
+        for record in &records {
+            for (key, _) in record {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                headers
+                    .iter()
+                    .map(|header| {
+                        record
+                            .iter()
+                            .find(|(key, _)| key == header)
+                            .map(|(_, value)| value.clone())
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self { headers, rows }
+    }
+
+    /// Markdown tables, one per status in order of appearance when the records have one.
+    fn markdown(&self) -> String {
+        let Some(status) = self.headers.iter().position(|h| h == STATUS_FIELD) else {
+            return markdown_table(&self.headers, self.rows.iter());
+        };
+
+        // The status of each group is already in its heading.
+        let columns = self
+            .headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| !header.starts_with("status_extra_info."))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let headers = columns
+            .iter()
+            .map(|&i| self.headers[i].clone())
+            .collect::<Vec<_>>();
+
+        let mut groups: Vec<(&str, Vec<Vec<String>>)> = Vec::new();
+        for row in &self.rows {
+            let cells = columns.iter().map(|&i| row[i].clone()).collect();
+
+            match groups.iter_mut().find(|(name, _)| *name == row[status]) {
+                Some((_, rows)) => rows.push(cells),
+                None => groups.push((&row[status], vec![cells])),
+            }
+        }
+
+        groups
+            .iter()
+            .map(|(name, rows)| {
+                format!(
+                    "## {name} ({})\n\n{}",
+                    rows.len(),
+                    markdown_table(&headers, rows.iter())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn markdown_table<'a>(headers: &[String], rows: impl Iterator<Item = &'a Vec<String>>) -> String {
+    let line = |cells: &[String]| {
+        let cells = cells
+            .iter()
+            .map(|cell| cell.replace('|', "\\|").replace('\n', "<br>"))
+            .collect::<Vec<_>>();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut table = line(headers);
+    table.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+    for row in rows {
+        table.push_str(&line(row));
+    }
+
+    table
+}
+
+/// Flattens a record into `(column, text)` pairs, naming nested fields `parent.child`.
+fn flatten(value: &Value) -> Vec<(String, String)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            value => out.push((prefix.to_owned(), cell(value))),
+        }
+    }
+
+    let mut out = Vec::new();
+    walk("", value, &mut out);
+    out
+}
+
+/// Text of a single cell. Lists are joined with commas; for lists of pairs, such as Taiga's
+/// `[tag, color]` tags, only the first element is kept.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(pair) => pair.first().map(cell).unwrap_or_default(),
+                Value::Object(_) => item.to_string(),
+                item => cell(item),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        value => value.to_string(),
+    }
+}
+
+/// A user-defined layout for story listings, in Jinja syntax.
+///
+/// A template that mentions `stories` or `groups` is rendered once over the whole listing
+/// (see [`ListingView`]); any other template is rendered once per story, over the fields of
+/// [`StoryView`](crate::integrations::taiga::StoryView), one story per line.
+pub struct Template {
+    source: String,
+}
+
+impl Template {
+    /// A template given on the command line, where `\t` and `\n` stand for a tab and a newline.
+    pub fn inline(source: &str) -> Self {
+        Self {
+            source: source.replace("\\t", "\t").replace("\\n", "\n"),
+        }
+    }
+
+    /// Reads a template from a file.
+    ///
+    /// # Errors
+    /// Returns `OutputError::TemplateFile` if the file cannot be read.
+    pub fn load(path: &Path) -> Result<Self, OutputError> {
+        let source = fs::read_to_string(path).map_err(|source| OutputError::TemplateFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Ok(Self { source })
+    }
+
+    /// Renders the template over the stories.
+    ///
+    /// # Errors
+    /// Returns `OutputError::Template` if the template is not valid or fails to render.
+    pub fn render(&self, stories: &[UserStory]) -> Result<String, OutputError> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.add_template("template", &self.source)?;
+        let template = env.get_template("template")?;
+
+        let variables = template.undeclared_variables(false);
+        if variables.contains("stories") || variables.contains("groups") {
+            return Ok(template.render(ListingView::new(stories))?);
+        }
+
+        let mut rendered = String::new();
+        for story in stories {
+            rendered.push_str(&template.render(story.view())?);
+            rendered.push('\n');
+        }
+
+        Ok(rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn story(reference: usize, status: &str, tags: Value) -> Value {
+        json!({
+            "ref": reference,
+            "subject": format!("Story {reference}"),
+            "status_extra_info": { "name": status, "is_closed": false },
+            "tags": tags,
+            "assigned_to": null,
+        })
+    }
+
+    #[test]
+    fn table_flattens_nested_fields_into_columns() {
+        let table = Table::new(&json!([
+            story(1, "New", json!([["auth", null], ["email", "#fff"]])),
+            { "ref": 2, "milestone_name": "Sprint 3" },
+        ]));
+
+        assert_eq!(
+            table.headers,
+            [
+                "ref",
+                "subject",
+                "status_extra_info.name",
+                "status_extra_info.is_closed",
+                "tags",
+                "assigned_to",
+                "milestone_name",
+            ]
+        );
+        assert_eq!(
+            table.rows[0],
+            ["1", "Story 1", "New", "false", "auth,email", "", ""]
+        );
+        assert_eq!(table.rows[1], ["2", "", "", "", "", "", "Sprint 3"]);
+    }
+
+    #[test]
+    fn table_of_a_single_record_has_one_row() {
+        let table = Table::new(&json!({ "id": 7, "name": "Gulfi" }));
+
+        assert_eq!(table.headers, ["id", "name"]);
+        assert_eq!(table.rows, [["7", "Gulfi"]]);
+    }
+
+    #[test]
+    fn markdown_groups_records_by_status() {
+        let table = Table::new(&json!([
+            story(1, "New", json!([])),
+            story(2, "Done", json!([])),
+            story(3, "New", json!(["a|b"])),
+        ]));
+
+        assert_eq!(
+            table.markdown(),
+            "## New (2)\n\n\
+             | ref | subject | tags | assigned_to |\n\
+             | --- | --- | --- | --- |\n\
+             | 1 | Story 1 |  |  |\n\
+             | 3 | Story 3 | a\\|b |  |\n\
+             \n\
+             ## Done (1)\n\n\
+             | ref | subject | tags | assigned_to |\n\
+             | --- | --- | --- | --- |\n\
+             | 2 | Story 2 |  |  |\n"
+        );
+    }
+
+    #[test]
+    fn markdown_without_status_is_a_single_table() {
+        let table = Table::new(&json!([{ "name": "Sprint 3", "notes": "two\nlines" }]));
+
+        assert_eq!(
+            table.markdown(),
+            "| name | notes |\n| --- | --- |\n| Sprint 3 | two<br>lines |\n"
+        );
+    }
+
+    fn stories() -> Vec<UserStory> {
+        let status = |name: &str| json!({ "name": name, "color": "#999", "is_closed": false });
+        let stories = json!([
+            {
+                "id": 101, "ref": 1, "subject": "Login", "status": 1,
+                "created_date": "2025-07-01T10:00:00Z", "status_extra_info": status("New"),
+                "assigned_to_extra_info": { "username": "jdoe" }, "tags": [["auth", null]],
+            },
+            {
+                "id": 102, "ref": 2, "subject": "Logout", "status": 2,
+                "created_date": "2025-07-02T10:00:00Z",
+                "status_extra_info": status("In progress"),
+            },
+        ]);
+
+        serde_json::from_value(stories).unwrap()
+    }
+
+    #[test]
+    fn template_is_rendered_once_per_story() {
+        let template = Template::inline(
+            "#{{ref}}\\t{{status}}\\t{{subject}}{% if assignee %} (@{{assignee}}){% endif %}",
+        );
+
+        assert_eq!(
+            template.render(&stories()).unwrap(),
+            "#1\tNew\tLogin (@jdoe)\n#2\tIn progress\tLogout\n"
+        );
+    }
+
+    #[test]
+    fn template_using_stories_is_rendered_once() {
+        let template = Template::inline(
+            "{{ stories | length }} stories: \
+             {% for story in stories %}{{ story.tags | join(\",\") }};{% endfor %}",
+        );
+
+        assert_eq!(template.render(&stories()).unwrap(), "2 stories: auth;;");
+    }
+
+    #[test]
+    fn template_errors_are_reported() {
+        let template = Template::inline("{% if ref %}");
+
+        assert!(matches!(
+            template.render(&stories()),
+            Err(OutputError::Template(_))
+        ));
+    }
+}
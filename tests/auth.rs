@@ -0,0 +1,39 @@
+//! Logging in to Taiga, against a stub server.
+
+mod common;
+
+use backlogr::integrations::taiga::{TaigaAPI, TaigaAPIError};
+use serde_json::json;
+
+use common::Stub;
+
+#[test]
+fn authenticate_sends_the_credentials() {
+    let stub = Stub::start(|request| match request.body["password"].as_str() {
+        Some("secret") => (
+            200,
+            json!({
+                "id": 99,
+                "username": "jdoe",
+                "email": "jdoe@example.com",
+                "auth_token": "token-1",
+                "refresh": "refresh-1",
+            }),
+        ),
+        _ => (401, json!({ "detail": "Invalid credentials" })),
+    });
+
+    let api = TaigaAPI::authenticate(&stub.api_url, "jdoe", "secret").unwrap();
+
+    assert_eq!(api.tokens().auth_token, "token-1");
+    assert_eq!(api.tokens().refresh, "refresh-1");
+    let request = &stub.requests()[0];
+    assert_eq!(request.path, "/api/v1/auth");
+    assert_eq!(
+        request.body,
+        json!({ "type": "normal", "username": "jdoe", "password": "secret" })
+    );
+
+    let result = TaigaAPI::authenticate(&stub.api_url, "jdoe", "wrong");
+    assert!(matches!(result, Err(TaigaAPIError::Authentication(_))));
+}
@@ -0,0 +1,54 @@
+//! Writing release notes from commits, against a stub server.
+
+mod common;
+
+use backlogr::{
+    git::Commit,
+    integrations::taiga::{ChangelogStyle, GroupBy, TaigaAPI},
+};
+use serde_json::json;
+
+use common::{story, tokens, Stub};
+
+#[test]
+fn only_the_stories_mentioned_are_read() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstories/by_ref?ref=42&project=7" => {
+            let mut story = story(1234, 42);
+            story["status_extra_info"]["is_closed"] = true.into();
+            story["tags"] = json!([["backend", null]]);
+            (200, story)
+        }
+        _ => (
+            404,
+            json!({ "detail": "No UserStory matches the given query." }),
+        ),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let commits = [
+        Commit {
+            sha: Some("a1b2c3d".to_owned()),
+            message: "fix: login on Safari, closes #42".to_owned(),
+        },
+        Commit {
+            sha: Some("d4e5f6a".to_owned()),
+            message: "docs: mention #404".to_owned(),
+        },
+    ];
+    let changelog = api
+        .changelog(7, &commits, GroupBy::Tag, false, Some("1.2.0".to_owned()))
+        .unwrap();
+
+    assert_eq!(changelog.unknown_refs(), [404]);
+    assert!(changelog
+        .render(ChangelogStyle::KeepAChangelog)
+        .ends_with("### Fixed\n\n- **backend**: Login fails on Safari (#42)\n"));
+    assert_eq!(
+        stub.log(),
+        [
+            "GET /api/v1/userstories/by_ref?ref=42&project=7",
+            "GET /api/v1/userstories/by_ref?ref=404&project=7",
+        ]
+    );
+}
@@ -0,0 +1,128 @@
+//! A stub Taiga API for the integration tests, answering with canned responses and recording
+//! the requests it receives.
+
+// Each test crate uses its own part of the helpers.
+#![allow(dead_code)]
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use backlogr::integrations::taiga::AuthTokens;
+use serde_json::{json, Value};
+
+/// A request as the stub received it.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: Value,
+}
+
+/// A stub Taiga API on a local port, answering each request with `respond`.
+pub struct Stub {
+    pub api_url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Stub {
+    pub fn start(respond: impl Fn(&Request) -> (u16, Value) + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}/api/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let request = read_request(&mut BufReader::new(&stream));
+                log.lock().unwrap().push(request.clone());
+
+                let (status, body) = respond(&request);
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        Self { api_url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// `METHOD path` of each request received so far.
+    pub fn log(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .map(|request| format!("{} {}", request.method, request.path))
+            .collect()
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Request {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap().to_owned();
+    let path = parts.next().unwrap().to_owned();
+
+    let mut authorization = None;
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        let (name, value) = header.split_once(':').unwrap();
+        match name.to_ascii_lowercase().as_str() {
+            "authorization" => authorization = Some(value.trim().to_owned()),
+            "content-length" => length = value.trim().parse().unwrap(),
+            _ => {}
+        }
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    Request {
+        method,
+        path,
+        authorization,
+        body,
+    }
+}
+
+/// A user story as Taiga returns it.
+pub fn story(id: usize, reference: usize) -> Value {
+    json!({
+        "id": id,
+        "ref": reference,
+        "subject": "Login fails on Safari",
+        "status": 1,
+        "created_date": "2025-05-01T10:00:00Z",
+        "version": 3,
+        "status_extra_info": { "name": "New", "color": "#999", "is_closed": false },
+    })
+}
+
+/// A session whose auth token is `auth_token`.
+pub fn tokens(auth_token: &str) -> AuthTokens {
+    AuthTokens {
+        auth_token: auth_token.to_owned(),
+        refresh: "refresh-1".to_owned(),
+    }
+}
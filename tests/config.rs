@@ -0,0 +1,107 @@
+//! Merging flags, environment variables and config profiles into the effective settings.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use backlogr::{
+    cli::{Cli, Format},
+    config::{ConfigError, Settings},
+};
+use clap::Parser;
+
+/// Variables read by `Cli` that would override the config files of these tests.
+const VARIABLES: [&str; 4] = [
+    "BACKLOGR_CONFIG",
+    "BACKLOGR_PROFILE",
+    "TAIGA_URL",
+    "PROJECT_NAME",
+];
+
+static ENVIRONMENT: Mutex<()> = Mutex::new(());
+
+/// Clears the variables read by `Cli` and keeps other tests from setting them until dropped.
+fn clean_environment() -> MutexGuard<'static, ()> {
+    let guard = ENVIRONMENT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for variable in VARIABLES {
+        std::env::remove_var(variable);
+    }
+    guard
+}
+
+/// Writes `content` to a config file named after the test.
+fn write_config(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("backlogr-{name}-{}.toml", std::process::id()));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// A config file with a `work` profile, used by default, and a `home` one.
+fn config_file(name: &str) -> PathBuf {
+    write_config(
+        name,
+        r#"
+default_profile = "work"
+
+[profiles.work]
+url = "https://taiga.example.com/api/v1/"
+project = "Gulfi"
+format = "json"
+
+[profiles.home]
+project = "Garden"
+"#,
+    )
+}
+
+fn resolve(config: &Path, args: &[&str]) -> Settings {
+    let config = config.to_str().unwrap();
+    let cli = Cli::parse_from(["backlogr", "--config", config].iter().chain(args));
+    Settings::resolve(&cli).unwrap()
+}
+
+#[test]
+fn flags_take_precedence_over_the_environment_and_the_environment_over_profiles() {
+    let _environment = clean_environment();
+
+    let empty = write_config("empty", "");
+    let settings = resolve(&empty, &[]);
+    fs::remove_file(&empty).unwrap();
+    assert!(matches!(
+        settings.project_name(),
+        Err(ConfigError::NoProject)
+    ));
+
+    let path = config_file("precedence");
+
+    let settings = resolve(&path, &[]);
+    assert_eq!(settings.project_name().unwrap(), "Gulfi");
+
+    std::env::set_var("PROJECT_NAME", "Shop");
+    let settings = resolve(&path, &[]);
+    assert_eq!(settings.project_name().unwrap(), "Shop");
+
+    let settings = resolve(&path, &["--project_name", "Blog"]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(settings.project_name().unwrap(), "Blog");
+}
+
+#[test]
+fn profiles_fill_in_what_flags_leave_out() {
+    let _environment = clean_environment();
+    let path = config_file("profiles");
+
+    let work = resolve(&path, &["--format", "csv"]);
+    assert_eq!(work.api_url, "https://taiga.example.com/api/v1");
+    assert!(matches!(work.format, Format::Csv));
+
+    // Settings the chosen profile lacks fall back to the defaults, not to the default profile.
+    let home = resolve(&path, &["--profile", "home"]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(home.api_url, "https://api.taiga.io/api/v1");
+    assert!(matches!(home.format, Format::Pretty));
+}
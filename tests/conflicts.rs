@@ -0,0 +1,107 @@
+//! Updating stories that someone else changes meanwhile, against a stub server.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use backlogr::integrations::taiga::{StoryChanges, TaigaAPI, TaigaAPIError};
+use serde_json::{json, Value};
+
+use common::{story, tokens, Request, Stub};
+
+/// Answers like Taiga for story 1234, which someone else saves with `edit` right before each
+/// of the first `edits` updates.
+fn contested(edit: fn(&mut Value), edits: usize) -> impl Fn(&Request) -> (u16, Value) {
+    let state = Arc::new(Mutex::new((story(1234, 42), edits)));
+
+    move |request| {
+        let (story, edits) = &mut *state.lock().unwrap();
+
+        if request.method == "PATCH" {
+            if *edits > 0 {
+                *edits -= 1;
+                edit(story);
+                story["version"] = (story["version"].as_u64().unwrap() + 1).into();
+            }
+            if request.body["version"] != story["version"] {
+                return (
+                    400,
+                    json!({ "version": "The version doesn't match with the current one" }),
+                );
+            }
+            story["subject"] = request.body["subject"].clone();
+            story["version"] = (story["version"].as_u64().unwrap() + 1).into();
+        }
+        (200, story.clone())
+    }
+}
+
+fn describe(story: &mut Value) {
+    story["description"] = "Seen on Safari 17 only.".into();
+}
+
+fn rename(story: &mut Value) {
+    story["subject"] = "Login fails on Safari 17".into();
+}
+
+fn changes() -> StoryChanges {
+    StoryChanges {
+        subject: Some("Login fails on WebKit".to_owned()),
+        ..StoryChanges::default()
+    }
+}
+
+#[test]
+fn updates_are_retried_on_top_of_changes_to_other_fields() {
+    let stub = Stub::start(contested(describe, 1));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let change = api.edit_story(7, 1234, &changes()).unwrap();
+
+    assert_eq!(change.version, Some(5));
+    assert_eq!(
+        stub.log(),
+        [
+            "GET /api/v1/userstories/1234",
+            "PATCH /api/v1/userstories/1234",
+            "GET /api/v1/userstories/1234",
+            "PATCH /api/v1/userstories/1234",
+        ]
+    );
+    assert_eq!(stub.requests()[3].body["version"], 4);
+}
+
+#[test]
+fn changes_to_the_same_field_are_not_overwritten() {
+    let stub = Stub::start(contested(rename, 1));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let error = api.edit_story(7, 1234, &changes()).unwrap_err();
+
+    assert!(matches!(
+        &error,
+        TaigaAPIError::VersionConflict(message) if message.starts_with("'subject' of")
+    ));
+    assert_eq!(error.exit_code(), 7);
+    assert_eq!(stub.log().len(), 3);
+}
+
+#[test]
+fn updates_give_up_on_stories_that_keep_changing() {
+    let stub = Stub::start(contested(describe, usize::MAX));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let error = api.edit_story(7, 1234, &changes()).unwrap_err();
+
+    assert!(matches!(
+        &error,
+        TaigaAPIError::VersionConflict(message) if message.ends_with("gave up after 3 retries")
+    ));
+    assert_eq!(
+        stub.log()
+            .iter()
+            .filter(|request| request.starts_with("PATCH "))
+            .count(),
+        4
+    );
+}
@@ -0,0 +1,53 @@
+//! Finding the story a previous `create --if-not-exists` made, against a stub server.
+
+mod common;
+
+use backlogr::integrations::taiga::{ChangeAction, TaigaAPI};
+use serde_json::json;
+
+use common::{story, tokens, Stub};
+
+#[test]
+fn stories_are_found_by_their_idempotency_tag() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstories?project=7&tags=key%3Adeploy-1.2&page=1&page_size=100" => {
+            // Taiga also matches tags that merely contain the one asked for.
+            let mut tagged = story(1234, 42);
+            tagged["tags"] = json!([["key:deploy-1.2", null]]);
+            let mut other = story(1235, 41);
+            other["tags"] = json!([["key:deploy-1.2.1", null]]);
+            (200, json!([tagged, other]))
+        }
+        _ => (500, json!({ "detail": "Unexpected request." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let change = api
+        .find_created_story(7, "Deploy v1.2", Some("Deploy-1.2"))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(change.reference, Some(42));
+    assert!(matches!(change.action, ChangeAction::Existing));
+}
+
+#[test]
+fn without_a_key_only_the_stories_taiga_finds_are_compared() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstories?project=7&q=Login%20fails%20on%20Safari&page=1&page_size=100" => {
+            let mut longer = story(1235, 40);
+            longer["subject"] = "Login fails on Safari 17".into();
+            (200, json!([story(1234, 42), longer, story(1236, 45)]))
+        }
+        _ => (500, json!({ "detail": "Unexpected request." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let change = api
+        .find_created_story(7, " Login fails on Safari ", None)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(change.reference, Some(42));
+    assert_eq!(stub.log().len(), 1);
+}
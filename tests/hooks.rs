@@ -0,0 +1,126 @@
+//! Installing the git hooks and reading what a push sends, in throwaway repositories.
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    sync::{Mutex, MutexGuard},
+};
+
+use backlogr::hooks::{self, ClosedPolicy, HookError};
+
+const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+
+/// Hooks work on the repository of the current directory, which the tests change one at a time.
+static CURRENT_DIR: Mutex<()> = Mutex::new(());
+
+/// Makes a new repository with the `commits` given the current directory, which other tests
+/// leave alone until the guard is dropped.
+fn repository(name: &str, commits: &[&str]) -> (MutexGuard<'static, ()>, PathBuf) {
+    let guard = CURRENT_DIR
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let dir = env::temp_dir().join(format!("backlogr-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    git(&["init", "--quiet"]);
+    for message in commits {
+        git(&["commit", "--quiet", "--allow-empty", "-m", message]);
+    }
+
+    (guard, dir)
+}
+
+fn git(args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Lau", "-c", "user.email=lau@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+fn messages(lines: &str) -> Vec<String> {
+    hooks::pushed_commits(lines)
+        .unwrap()
+        .into_iter()
+        .map(|commit| commit.message)
+        .collect()
+}
+
+#[test]
+fn hooks_replace_their_own_but_not_others() {
+    let (_current_dir, dir) = repository("install", &[]);
+    let hooks_dir = PathBuf::from(".git/hooks");
+
+    let installed = hooks::install(ClosedPolicy::Fail, false).unwrap();
+    assert_eq!(
+        installed,
+        [hooks_dir.join("commit-msg"), hooks_dir.join("pre-push")]
+    );
+    let commit_msg = fs::read_to_string(&installed[0]).unwrap();
+    assert!(commit_msg.ends_with("exec backlogr hooks check --file \"$1\" --closed fail\n"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&installed[1]).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    // Installing again replaces the hooks written by backlogr.
+    hooks::install(ClosedPolicy::Warn, false).unwrap();
+    assert!(fs::read_to_string(&installed[0])
+        .unwrap()
+        .ends_with("--closed warn\n"));
+
+    fs::write(&installed[1], "#!/bin/sh\nmake test\n").unwrap();
+    assert!(matches!(
+        hooks::install(ClosedPolicy::Warn, false),
+        Err(HookError::Exists { path }) if path == installed[1]
+    ));
+    hooks::install(ClosedPolicy::Warn, true).unwrap();
+    let pre_push = fs::read_to_string(&installed[1]).unwrap();
+
+    env::set_current_dir(env::temp_dir()).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(pre_push.contains("--pre-push"));
+}
+
+#[test]
+fn hooks_go_to_the_configured_hooks_path() {
+    let (_current_dir, dir) = repository("hooks-path", &[]);
+    git(&["config", "core.hooksPath", "githooks"]);
+
+    let installed = hooks::install(ClosedPolicy::Warn, false).unwrap();
+
+    env::set_current_dir(env::temp_dir()).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(installed[0], PathBuf::from("githooks/commit-msg"));
+}
+
+#[test]
+fn pushes_send_the_commits_the_remote_lacks() {
+    let (_current_dir, dir) = repository("push", &["feat: login", "fix: logout", "docs: readme"]);
+    let head = git(&["rev-parse", "HEAD"]);
+    let first = git(&["rev-parse", "HEAD~2"]);
+
+    let update = format!("refs/heads/main {head} refs/heads/main {first}");
+    let new_branch = format!("refs/heads/main {head} refs/heads/main {ZERO_SHA}");
+    let deletion = format!("(delete) {ZERO_SHA} refs/heads/old {first}");
+
+    let updated = messages(&update);
+    let created = messages(&new_branch);
+    let deleted = messages(&deletion);
+
+    env::set_current_dir(env::temp_dir()).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(updated, ["fix: logout", "docs: readme"]);
+    // Without remote branches, all of a new branch is pushed.
+    assert_eq!(created, ["feat: login", "fix: logout", "docs: readme"]);
+    assert!(deleted.is_empty());
+}
@@ -0,0 +1,85 @@
+//! Importing stories in bulk, against a stub server.
+
+mod common;
+
+use backlogr::integrations::taiga::{NewStory, TaigaAPI};
+use serde_json::{json, Value};
+
+use common::{tokens, Stub};
+
+fn new_story(subject: &str) -> NewStory {
+    serde_json::from_value(json!({ "subject": subject })).unwrap()
+}
+
+/// `(ref, result)` of each row of a report.
+fn results(report: &Value) -> Vec<(Value, &str)> {
+    report
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| (row["ref"].clone(), row["result"].as_str().unwrap()))
+        .collect()
+}
+
+#[test]
+fn stories_skipped_by_bulk_create_fail_on_their_own_row() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstory-statuses?project=7" => {
+            (200, json!([{ "id": 1, "name": "New", "is_closed": false }]))
+        }
+        // Taiga drops the second story.
+        "/api/v1/userstories/bulk_create" => (
+            200,
+            json!([
+                { "id": 11, "ref": 41, "version": 1, "subject": "Login" },
+                { "id": 13, "ref": 43, "version": 1, "subject": "Logout" },
+            ]),
+        ),
+        _ => (404, json!({ "detail": "Not found." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let stories = [new_story("Login"), new_story("Signup"), new_story("Logout")];
+    let report = serde_json::to_value(api.import_stories(7, &stories, false)).unwrap();
+
+    assert_eq!(
+        results(&report),
+        [
+            (json!(41), "created"),
+            (Value::Null, "failed"),
+            (json!(43), "created"),
+        ]
+    );
+    assert_eq!(
+        stub.requests()[1].body["bulk_stories"],
+        "Login\nSignup\nLogout"
+    );
+}
+
+#[test]
+fn subjects_with_line_breaks_are_rejected_before_creating_anything() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstory-statuses?project=7" => {
+            (200, json!([{ "id": 1, "name": "New", "is_closed": false }]))
+        }
+        _ => (500, json!({ "detail": "Nothing should be created." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let stories = [
+        new_story("Login"),
+        new_story("Sign\nup"),
+        new_story("Log\rout"),
+    ];
+    let report = serde_json::to_value(api.import_stories(7, &stories, false)).unwrap();
+
+    assert_eq!(
+        results(&report),
+        [
+            (Value::Null, "valid"),
+            (Value::Null, "invalid"),
+            (Value::Null, "invalid"),
+        ]
+    );
+    assert!(stub.log().iter().all(|request| request.starts_with("GET ")));
+}
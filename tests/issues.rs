@@ -0,0 +1,94 @@
+//! Listing and updating issues, against a stub server.
+
+mod common;
+
+use backlogr::integrations::taiga::{IssueChanges, Priority, TaigaAPI, TaigaAPIError};
+use serde_json::{json, Value};
+
+use common::{tokens, Stub};
+
+fn issue() -> Value {
+    json!({
+        "id": 31,
+        "ref": 58,
+        "subject": "Login fails on Safari",
+        "status": 1,
+        "type": 1,
+        "severity": 2,
+        "priority": null,
+        "created_date": "2025-05-01T10:00:00Z",
+        "version": 4,
+        "status_extra_info": { "name": "New", "color": "#999", "is_closed": false },
+    })
+}
+
+fn respond(path: &str) -> (u16, Value) {
+    match path {
+        "/api/v1/issues?project=7&page=1&page_size=100" => (200, json!([issue()])),
+        "/api/v1/issues/31" => (200, issue()),
+        "/api/v1/issue-types?project=7" => (200, json!([{ "id": 1, "name": "Bug" }])),
+        "/api/v1/severities?project=7" => (
+            200,
+            json!([{ "id": 1, "name": "Minor" }, { "id": 2, "name": "Critical" }]),
+        ),
+        "/api/v1/priorities?project=7" => (200, json!([{ "id": 5, "name": "High" }])),
+        _ => (404, json!({ "detail": "Not found." })),
+    }
+}
+
+#[test]
+fn listed_issues_carry_the_names_of_their_attributes() {
+    let stub = Stub::start(|request| respond(&request.path));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let issues = serde_json::to_value(api.list_all_issues(7).unwrap()).unwrap();
+
+    assert_eq!(issues[0]["type_name"], "Bug");
+    assert_eq!(issues[0]["severity_name"], "Critical");
+    assert_eq!(issues[0]["priority_name"], Value::Null);
+}
+
+#[test]
+fn updates_send_only_the_given_fields() {
+    let stub = Stub::start(|request| match request.method.as_str() {
+        "PATCH" => {
+            let mut issue = issue();
+            issue["priority"] = request.body["priority"].clone();
+            issue["version"] = 5.into();
+            (200, issue)
+        }
+        _ => respond(&request.path),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let changes = IssueChanges {
+        priority: Some(Priority("high".to_owned())),
+        ..IssueChanges::default()
+    };
+    let change = api.update_issue(7, 31, &changes).unwrap();
+
+    assert_eq!(change.version, Some(5));
+    let patch = stub
+        .requests()
+        .into_iter()
+        .find(|request| request.method == "PATCH")
+        .unwrap();
+    assert_eq!(patch.body, json!({ "priority": 5, "version": 4 }));
+}
+
+#[test]
+fn unknown_attribute_names_list_the_available_ones() {
+    let stub = Stub::start(|request| respond(&request.path));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let changes = IssueChanges {
+        priority: Some(Priority("Urgent".to_owned())),
+        ..IssueChanges::default()
+    };
+
+    assert!(matches!(
+        api.update_issue(7, 31, &changes),
+        Err(TaigaAPIError::ValueNotFound(message)) if message.ends_with("Available: High")
+    ));
+    assert!(stub.log().iter().all(|request| request.starts_with("GET ")));
+}
@@ -0,0 +1,247 @@
+//! Syncing a backlog file with a project, against a stub server that keeps the stories.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use backlogr::integrations::taiga::{BacklogStory, TaigaAPI};
+use serde_json::{json, Value};
+
+use common::{tokens, Request, Stub};
+
+const STATUSES: [(usize, &str, bool); 3] = [
+    (1, "New", false),
+    (2, "In progress", false),
+    (3, "Done", true),
+];
+
+fn status_extra_info(status: usize) -> Value {
+    let (_, name, is_closed) = STATUSES[status - 1];
+    json!({ "name": name, "is_closed": is_closed })
+}
+
+fn stored(id: usize, subject: &str, description: &str, tags: &[&str]) -> Value {
+    json!({
+        "id": id,
+        "ref": id,
+        "subject": subject,
+        "description": description,
+        "status": 1,
+        "status_extra_info": status_extra_info(1),
+        "tags": tags.iter().map(|tag| json!([tag, null])).collect::<Vec<_>>(),
+        "version": 1,
+    })
+}
+
+type Stories = Arc<Mutex<Vec<Value>>>;
+
+/// Answers like Taiga for the stories of project 7, kept in `stories`.
+fn project(stories: &Stories) -> impl Fn(&Request) -> (u16, Value) {
+    let stories = Arc::clone(stories);
+
+    move |request| {
+        let mut stories = stories.lock().unwrap();
+        let path = request.path.trim_start_matches("/api/v1/");
+
+        match (request.method.as_str(), path) {
+            ("GET", "userstory-statuses?project=7") => {
+                let statuses = STATUSES
+                    .iter()
+                    .map(
+                        |(id, name, closed)| json!({ "id": id, "name": name, "is_closed": closed }),
+                    )
+                    .collect::<Vec<_>>();
+                (200, json!(statuses))
+            }
+            ("GET", path) if path.starts_with("userstories?project=7") => {
+                // Listings leave the description out.
+                let listed = stories
+                    .iter()
+                    .map(|story| {
+                        let mut story = story.clone();
+                        story.as_object_mut().unwrap().remove("description");
+                        story
+                    })
+                    .collect::<Vec<_>>();
+                (200, json!(listed))
+            }
+            ("POST", "userstories") => {
+                let id = stories.len() + 1;
+                let body = &request.body;
+                let tags = body["tags"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|tag| tag.as_str().unwrap())
+                    .collect::<Vec<_>>();
+                let mut story = stored(
+                    id,
+                    body["subject"].as_str().unwrap(),
+                    body["description"].as_str().unwrap_or_default(),
+                    &tags,
+                );
+                story["status"] = body["status"].clone();
+                story["status_extra_info"] =
+                    status_extra_info(body["status"].as_u64().unwrap() as usize);
+                stories.push(story.clone());
+                (201, story)
+            }
+            (method, path) => {
+                let Some(story) = path
+                    .strip_prefix("userstories/")
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .and_then(|id| stories.get_mut(id - 1))
+                else {
+                    return (404, json!({ "detail": "Not found." }));
+                };
+
+                if method == "PATCH" {
+                    if request.body["version"] != story["version"] {
+                        return (400, json!({ "version": "The version doesn't match." }));
+                    }
+                    for (key, value) in request.body.as_object().unwrap() {
+                        story[key] = match key.as_str() {
+                            "tags" => value
+                                .as_array()
+                                .unwrap()
+                                .iter()
+                                .map(|tag| json!([tag, null]))
+                                .collect(),
+                            _ => value.clone(),
+                        };
+                    }
+                    story["status_extra_info"] =
+                        status_extra_info(story["status"].as_u64().unwrap() as usize);
+                    story["version"] = (story["version"].as_u64().unwrap() + 1).into();
+                }
+                (200, story.clone())
+            }
+        }
+    }
+}
+
+fn backlog_story(id: &str, subject: &str, description: &str, tags: &[&str]) -> BacklogStory {
+    BacklogStory {
+        id: id.to_owned(),
+        subject: subject.to_owned(),
+        description: Some(description.to_owned()),
+        status: None,
+        tags: tags.iter().map(|tag| (*tag).to_owned()).collect(),
+        assignee: None,
+        sprint: None,
+        epic: None,
+    }
+}
+
+#[test]
+fn applying_a_plan_twice_changes_nothing_the_second_time() {
+    let stories = Arc::new(Mutex::new(vec![
+        stored(1, "Login", "Old text", &["ext:auth-login"]),
+        stored(2, "Spike the login flow", "", &["ext:old-spike"]),
+        stored(3, "Not managed", "", &["ui"]),
+    ]));
+    let stub = Stub::start(project(&stories));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let mut login = backlog_story("Auth-Login", "Login with OAuth", "New text", &["Auth"]);
+    login.status = Some("wip".to_owned());
+    let backlog = [
+        login,
+        backlog_story(
+            "auth-logout",
+            "Logout everywhere",
+            "Revoke every session.",
+            &[],
+        ),
+    ];
+
+    let plan = api.plan_backlog(7, &backlog).unwrap();
+    let report = serde_json::to_value(&plan).unwrap();
+    assert_eq!(
+        report
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| (
+                step["action"].as_str().unwrap(),
+                step["external_id"].as_str().unwrap()
+            ))
+            .collect::<Vec<_>>(),
+        [
+            ("update", "Auth-Login"),
+            ("create", "auth-logout"),
+            ("close", "old-spike")
+        ]
+    );
+    assert_eq!(
+        report[0]["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|change| change["field"].as_str().unwrap())
+            .collect::<Vec<_>>(),
+        ["subject", "description", "status", "tags"]
+    );
+
+    let plan = api.apply_plan(7, plan);
+    assert_eq!(plan.exit_code(), None);
+
+    let applied = stub.requests().len();
+    let plan = api.plan_backlog(7, &backlog).unwrap();
+    assert!(plan.is_empty(), "{plan}");
+
+    // The descriptions written by the first run are known, nothing is read one by one.
+    let second_run = &stub.log()[applied..];
+    assert!(
+        second_run
+            .iter()
+            .all(|request| !request.starts_with("GET /api/v1/userstories/")),
+        "{second_run:?}"
+    );
+
+    let requests = stub.requests();
+    let created = &requests[..applied]
+        .iter()
+        .find(|request| request.method == "POST")
+        .unwrap()
+        .body;
+    assert_eq!(created["tags"], json!(["ext:auth-logout"]));
+    assert_eq!(created["status"], 1);
+}
+
+#[test]
+fn updates_keep_changes_made_in_taiga_meanwhile() {
+    let stories = Arc::new(Mutex::new(vec![stored(
+        1,
+        "Login",
+        "Old text",
+        &["ext:auth-login"],
+    )]));
+    let stub = Stub::start(project(&stories));
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let backlog = [backlog_story(
+        "auth-login",
+        "Login with OAuth",
+        "New text",
+        &[],
+    )];
+    let plan = api.plan_backlog(7, &backlog).unwrap();
+
+    // Someone starts working on the story before the plan is applied.
+    {
+        let story = &mut stories.lock().unwrap()[0];
+        story["status"] = 2.into();
+        story["version"] = 2.into();
+    }
+
+    // The detail read for the plan is the base, so its description is not taken for a clash.
+    let plan = api.apply_plan(7, plan);
+    assert_eq!(plan.exit_code(), None);
+
+    let story = &stories.lock().unwrap()[0];
+    assert_eq!(story["subject"], "Login with OAuth");
+    assert_eq!(story["description"], "New text");
+    assert_eq!(story["status"], 2);
+    assert_eq!(story["version"], 3);
+}
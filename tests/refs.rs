@@ -0,0 +1,156 @@
+//! Looking user stories up by ref, against a stub server.
+
+mod common;
+
+use backlogr::{
+    cache::RefCache,
+    integrations::taiga::{TaigaAPI, TaigaAPIError},
+};
+use serde_json::json;
+
+use common::{story, tokens, Stub};
+
+#[test]
+fn stories_are_looked_up_by_ref() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstories/by_ref?ref=42&project=7" => (200, story(1234, 42)),
+        _ => (
+            404,
+            json!({ "detail": "No UserStory matches the given query." }),
+        ),
+    });
+
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    assert_eq!(api.get_story_id(7, 42).unwrap(), 1234);
+    assert!(matches!(
+        api.get_story_id(7, 43),
+        Err(TaigaAPIError::StoryNotFound(_))
+    ));
+    // Known refs are not looked up again.
+    assert_eq!(api.get_story_id(7, 42).unwrap(), 1234);
+
+    assert_eq!(
+        stub.log(),
+        [
+            "GET /api/v1/userstories/by_ref?ref=42&project=7",
+            "GET /api/v1/userstories/by_ref?ref=43&project=7",
+        ]
+    );
+    assert!(stub
+        .requests()
+        .iter()
+        .all(|request| request.authorization.as_deref() == Some("Bearer token-1")));
+}
+
+#[test]
+fn stale_cached_ids_are_looked_up_again() {
+    let stub = Stub::start(
+        |request| match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/api/v1/userstories/by_ref?ref=42&project=7") => (200, story(1234, 42)),
+            ("DELETE", "/api/v1/userstories/1234") => (204, json!(null)),
+            _ => (
+                404,
+                json!({ "detail": "No UserStory matches the given query." }),
+            ),
+        },
+    );
+
+    let mut cache = RefCache::default();
+    cache.insert_story(&stub.api_url, 7, 42, 999);
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1")).with_ref_cache(cache);
+
+    let id = api.get_story_id(7, 42).unwrap();
+    assert_eq!(id, 999);
+    assert!(stub.log().is_empty());
+
+    let change = api.delete_story(id).unwrap();
+    assert_eq!(change.id, 1234);
+
+    assert_eq!(
+        stub.log(),
+        [
+            "GET /api/v1/userstories/999",
+            "GET /api/v1/userstories/by_ref?ref=42&project=7",
+            "DELETE /api/v1/userstories/1234",
+        ]
+    );
+}
+
+#[test]
+fn many_refs_are_picked_from_one_listing() {
+    let stub = Stub::start(
+        |request| match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/api/v1/userstories?project=7&page=1&page_size=100") => (
+                200,
+                json!((40..=49)
+                    .map(|reference| story(reference + 1000, reference))
+                    .collect::<Vec<_>>()),
+            ),
+            ("DELETE", _) => (204, json!(null)),
+            _ => (500, json!({ "detail": "Unexpected request." })),
+        },
+    );
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let refs = [42, 43, 44, 45, 46, 47, 98, 99];
+    let selection = api.select_stories(7, &refs, None).unwrap();
+    let report = serde_json::to_value(api.delete_stories(&selection)).unwrap();
+
+    let results = report
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                entry["ref"].as_u64().unwrap(),
+                entry["result"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        [
+            (98, "not_found"),
+            (99, "not_found"),
+            (42, "deleted"),
+            (43, "deleted"),
+            (44, "deleted"),
+            (45, "deleted"),
+            (46, "deleted"),
+            (47, "deleted"),
+        ]
+    );
+    assert_eq!(
+        stub.log()
+            .iter()
+            .filter(|request| request.starts_with("GET "))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn stories_looked_up_by_ref_are_not_fetched_again() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/userstories/by_ref?ref=42&project=7" => {
+            let mut story = story(1234, 42);
+            story["modified_date"] = "2025-05-02T10:00:00Z".into();
+            (200, story)
+        }
+        "/api/v1/userstories/1234" => (500, json!({ "detail": "Already read." })),
+        "/api/v1/userstories/custom-attributes-values/1234" => {
+            (200, json!({ "attributes_values": {} }))
+        }
+        _ => (200, json!([])),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let id = api.get_story_id(7, 42).unwrap();
+    let story = serde_json::to_value(api.get_story(7, id).unwrap()).unwrap();
+
+    assert_eq!(story["ref"], 42);
+    assert!(!stub
+        .log()
+        .contains(&"GET /api/v1/userstories/1234".to_owned()));
+}
@@ -0,0 +1,105 @@
+//! Refreshing expired sessions, against a stub server.
+
+mod common;
+
+use backlogr::{
+    credentials::{Credentials, Session},
+    integrations::taiga::{TaigaAPI, TaigaAPIError},
+};
+use serde_json::json;
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use common::{story, tokens, Stub};
+
+#[test]
+fn expired_sessions_are_refreshed_and_the_request_retried() {
+    let stub =
+        Stub::start(
+            |request| match (request.path.as_str(), request.authorization.as_deref()) {
+                ("/api/v1/auth/refresh", _) if request.body["refresh"] == "refresh-1" => (
+                    200,
+                    json!({ "auth_token": "token-2", "refresh": "refresh-2" }),
+                ),
+                (_, Some("Bearer token-2")) => (200, story(1234, 42)),
+                _ => (401, json!({ "detail": "Token expired" })),
+            },
+        );
+
+    let refreshed = Arc::new(Mutex::new(None));
+    let hook = Arc::clone(&refreshed);
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"))
+        .on_refresh(move |tokens| *hook.lock().unwrap() = Some(tokens.auth_token.clone()));
+
+    assert_eq!(api.get_story_id(7, 42).unwrap(), 1234);
+
+    assert_eq!(
+        stub.log(),
+        [
+            "GET /api/v1/userstories/by_ref?ref=42&project=7",
+            "POST /api/v1/auth/refresh",
+            "GET /api/v1/userstories/by_ref?ref=42&project=7",
+        ]
+    );
+    assert_eq!(
+        stub.requests()[2].authorization.as_deref(),
+        Some("Bearer token-2")
+    );
+    assert_eq!(api.tokens().auth_token, "token-2");
+    assert_eq!(refreshed.lock().unwrap().as_deref(), Some("token-2"));
+}
+
+#[test]
+fn refresh_failures_ask_to_log_in_again() {
+    let stub = Stub::start(|_| (401, json!({ "detail": "Token expired" })));
+
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let result = api.get_story_id(7, 42);
+    assert!(
+        matches!(&result, Err(TaigaAPIError::Authentication(message)) if message.contains("backlogr login"))
+    );
+}
+
+#[test]
+fn sessions_are_only_used_for_their_user() {
+    let session = Session {
+        username: "alice".to_owned(),
+        tokens: tokens("token-1"),
+    };
+
+    assert!(session.belongs_to(Some("alice")));
+    assert!(session.belongs_to(None));
+    assert!(!session.belongs_to(Some("bob")));
+}
+
+#[cfg(unix)]
+#[test]
+fn the_credentials_file_is_kept_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("backlogr-session-{}", std::process::id()));
+    std::env::set_var("XDG_CONFIG_HOME", &dir);
+
+    // A file left readable by others, e.g. by an older version.
+    let path = Credentials::path().unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "{}").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let mut credentials = Credentials::load().unwrap();
+    credentials.insert(
+        "https://taiga.example/api/v1",
+        Session {
+            username: "alice".to_owned(),
+            tokens: tokens("token-1"),
+        },
+    );
+    credentials.save().unwrap();
+
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(mode & 0o777, 0o600);
+}
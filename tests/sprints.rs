@@ -0,0 +1,38 @@
+//! Picking the current sprint, against a stub server.
+
+mod common;
+
+use backlogr::{dates::Date, integrations::taiga::TaigaAPI};
+use serde_json::{json, Value};
+
+use common::{tokens, Stub};
+
+fn sprint(id: usize, start: Date, finish: Date, closed: bool) -> Value {
+    json!({
+        "id": id,
+        "name": format!("Sprint {id}"),
+        "estimated_start": start.to_string(),
+        "estimated_finish": finish.to_string(),
+        "closed": closed,
+        "total_points": null,
+        "closed_points": null,
+    })
+}
+
+#[test]
+fn the_current_sprint_is_the_open_one_running_today() {
+    let today = Date::today();
+    let sprints = json!([
+        sprint(3, today.add_days(1), today.add_days(14), false),
+        // Closed early, its dates still include today.
+        sprint(1, today.add_days(-13), today, true),
+        sprint(2, today.add_days(-6), today.add_days(7), false),
+    ]);
+    let stub = Stub::start(move |request| match request.path.as_str() {
+        path if path.starts_with("/api/v1/milestones?project=7") => (200, sprints.clone()),
+        _ => (404, json!({ "detail": "Not found." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    assert_eq!(api.get_current_sprint(7).unwrap().id(), 2);
+}
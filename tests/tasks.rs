@@ -0,0 +1,64 @@
+//! Listing and creating tasks, against a stub server.
+
+mod common;
+
+use backlogr::integrations::taiga::{Status, TaigaAPI};
+use serde_json::json;
+
+use common::{tokens, Stub};
+
+fn task(id: usize, reference: usize) -> serde_json::Value {
+    json!({
+        "id": id,
+        "ref": reference,
+        "subject": "Write the migration",
+        "status": 21,
+        "created_date": "2025-06-01T10:00:00Z",
+        "user_story": 1234,
+        "user_story_extra_info": { "id": 1234, "ref": 42, "subject": "Login" },
+        "status_extra_info": { "name": "New", "color": "#999", "is_closed": false },
+        "version": 1,
+    })
+}
+
+#[test]
+fn tasks_are_listed_for_one_story() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/tasks?project=7&user_story=1234&page=1&page_size=100" => {
+            (200, json!([task(5, 50), task(6, 51)]))
+        }
+        _ => (500, json!({ "detail": "Unexpected request." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let tasks = serde_json::to_value(api.list_all_tasks(7, Some(1234)).unwrap()).unwrap();
+
+    assert_eq!(tasks[0]["ref"], 50);
+    assert_eq!(tasks[1]["user_story_extra_info"]["ref"], 42);
+}
+
+#[test]
+fn tasks_are_created_in_the_status_a_category_stands_for() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/api/v1/task-statuses?project=7" => (
+            200,
+            json!([
+                { "id": 21, "name": "New", "is_closed": false },
+                { "id": 22, "name": "In progress", "is_closed": false },
+                { "id": 23, "name": "Closed", "is_closed": true },
+            ]),
+        ),
+        "/api/v1/tasks" => (201, task(5, 50)),
+        _ => (500, json!({ "detail": "Unexpected request." })),
+    });
+    let api = TaigaAPI::from_tokens(&stub.api_url, tokens("token-1"));
+
+    let change = api
+        .create_task(7, 1234, "Write the migration", "", &Status::Done)
+        .unwrap();
+
+    assert_eq!(change.reference, Some(50));
+    let created = &stub.requests()[1].body;
+    assert_eq!(created["status"], 23);
+    assert_eq!(created["user_story"], 1234);
+}